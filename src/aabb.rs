@@ -0,0 +1,248 @@
+use crate::vector::{Scalar, Vector};
+
+/// Axis-aligned box (hyper-rectangle) [min, max) in N dimensions.
+///
+/// Like Interval, boxes are half-open so that volumes and splits need no +1 corrections.
+/// Closed integer boxes [min, max] are created with Aabb::closed.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Aabb<const N: usize, T: Scalar> {
+    pub min: Vector<N, T>,
+    pub max: Vector<N, T>,
+}
+
+fn elementwise<const N: usize, T: Scalar>(
+    a: &Vector<N, T>,
+    b: &Vector<N, T>,
+    f: impl Fn(T, T) -> T,
+) -> Vector<N, T> {
+    a.iter().zip(b.iter()).map(|(x, y)| f(*x, *y)).collect()
+}
+
+fn min_t<T: PartialOrd>(a: T, b: T) -> T {
+    if b < a { b } else { a }
+}
+
+fn max_t<T: PartialOrd>(a: T, b: T) -> T {
+    if b > a { b } else { a }
+}
+
+impl<const N: usize, T: Scalar + PartialOrd> Aabb<N, T> {
+    /// Create half-open box [min, max).
+    pub fn new(min: Vector<N, T>, max: Vector<N, T>) -> Self {
+        Aabb { min, max }
+    }
+
+    /// Create closed box [min, max].
+    pub fn closed(min: Vector<N, T>, max: Vector<N, T>) -> Self {
+        Aabb {
+            min,
+            max: max + T::one(),
+        }
+    }
+
+    /// Check if box contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.min
+            .iter()
+            .zip(self.max.iter())
+            .any(|(lo, hi)| hi <= lo)
+    }
+
+    /// Side lengths.
+    pub fn extent(&self) -> Vector<N, T> {
+        self.max - self.min
+    }
+
+    /// Product of side lengths, zero if empty.
+    pub fn volume(&self) -> T {
+        if self.is_empty() {
+            return T::zero();
+        }
+        self.extent().iter().fold(T::one(), |acc, x| acc * *x)
+    }
+
+    pub fn contains(&self, p: &Vector<N, T>) -> bool {
+        (0..N).all(|i| self.min[i] <= p[i] && p[i] < self.max[i])
+    }
+
+    /// Check if boxes share at least one point.
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..N).all(|i| self.min[i] < other.max[i] && other.min[i] < self.max[i])
+    }
+
+    /// Intersection of two boxes, None if they don't overlap.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let ret = Aabb {
+            min: elementwise(&self.min, &other.min, max_t),
+            max: elementwise(&self.max, &other.max, min_t),
+        };
+        (!ret.is_empty()).then_some(ret)
+    }
+
+    /// Split self \ other into at most 2N disjoint boxes.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let Some(isect) = self.intersection(other) else {
+            return vec![*self];
+        };
+
+        // peel off slabs below and above the intersection, one axis at a time
+        let mut ret = Vec::with_capacity(2 * N);
+        let mut rest = *self;
+        for i in 0..N {
+            if rest.min[i] < isect.min[i] {
+                let mut below = rest;
+                below.max[i] = isect.min[i];
+                ret.push(below);
+                rest.min[i] = isect.min[i];
+            }
+            if isect.max[i] < rest.max[i] {
+                let mut above = rest;
+                above.min[i] = isect.max[i];
+                ret.push(above);
+                rest.max[i] = isect.max[i];
+            }
+        }
+        ret
+    }
+}
+
+/// Union of boxes, stored as a list of disjoint boxes.
+#[derive(Debug, Clone)]
+pub struct AabbSet<const N: usize, T: Scalar> {
+    boxes: Vec<Aabb<N, T>>,
+}
+
+impl<const N: usize, T: Scalar + PartialOrd> Default for AabbSet<N, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, T: Scalar + PartialOrd> AabbSet<N, T> {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        AabbSet { boxes: Vec::new() }
+    }
+
+    /// Iterate over the disjoint boxes making up the set.
+    pub fn iter(&self) -> impl Iterator<Item = &Aabb<N, T>> {
+        self.boxes.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+
+    /// Add all points in b.
+    pub fn insert(&mut self, b: Aabb<N, T>) {
+        if b.is_empty() {
+            return;
+        }
+        // only add the parts of b that are not yet covered
+        let mut pieces = vec![b];
+        for existing in self.boxes.iter() {
+            pieces = pieces
+                .iter()
+                .flat_map(|piece| piece.subtract(existing))
+                .collect();
+            if pieces.is_empty() {
+                return;
+            }
+        }
+        self.boxes.extend(pieces);
+    }
+
+    /// Remove all points in b.
+    pub fn remove(&mut self, b: &Aabb<N, T>) {
+        self.boxes = self.boxes.iter().flat_map(|x| x.subtract(b)).collect();
+    }
+
+    pub fn contains(&self, p: &Vector<N, T>) -> bool {
+        self.boxes.iter().any(|b| b.contains(p))
+    }
+
+    /// Total covered volume.
+    pub fn volume(&self) -> T {
+        self.boxes.iter().map(|b| b.volume()).sum()
+    }
+}
+
+impl<const N: usize, T: Scalar + PartialOrd> FromIterator<Aabb<N, T>> for AabbSet<N, T> {
+    fn from_iter<I: IntoIterator<Item = Aabb<N, T>>>(iter: I) -> Self {
+        let mut ret = AabbSet::new();
+        for b in iter {
+            ret.insert(b);
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::{Vec2i, Vec3i};
+
+    #[test]
+    fn test_aabb() {
+        let a = Aabb::closed(Vec3i::new(10, 10, 10), Vec3i::new(12, 12, 12));
+        assert_eq!(a.volume(), 27);
+        assert!(a.contains(&Vec3i::new(12, 10, 11)));
+        assert!(!a.contains(&Vec3i::new(13, 10, 11)));
+
+        let b = Aabb::closed(Vec3i::new(11, 11, 11), Vec3i::new(13, 13, 13));
+        assert!(a.intersects(&b));
+        assert_eq!(
+            a.intersection(&b),
+            Some(Aabb::closed(Vec3i::new(11, 11, 11), Vec3i::new(12, 12, 12)))
+        );
+
+        let c = Aabb::new(Vec3i::new(13, 0, 0), Vec3i::new(20, 20, 20));
+        assert!(!a.intersects(&c));
+        assert_eq!(a.intersection(&c), None);
+        assert!(Aabb::new(Vec2i::new(0, 0), Vec2i::new(0, 5)).is_empty());
+    }
+
+    #[test]
+    fn test_aabb_subtract() {
+        let a = Aabb::new(Vec2i::new(0, 0), Vec2i::new(10, 10));
+        let hole = Aabb::new(Vec2i::new(2, 3), Vec2i::new(5, 7));
+        let pieces = a.subtract(&hole);
+        assert_eq!(pieces.len(), 4);
+        assert_eq!(pieces.iter().map(|p| p.volume()).sum::<i64>(), 100 - 12);
+        for (i, p) in pieces.iter().enumerate() {
+            assert!(!p.intersects(&hole));
+            assert!(pieces[i + 1..].iter().all(|q| !p.intersects(q)));
+        }
+
+        // subtracting a disjoint box leaves it unchanged, a covering box removes all
+        let far = Aabb::new(Vec2i::new(20, 20), Vec2i::new(30, 30));
+        assert_eq!(a.subtract(&far), vec![a]);
+        assert!(hole.subtract(&a).is_empty());
+    }
+
+    #[test]
+    fn test_aabb_set() {
+        // reboot steps from the small 2021 day 22 example
+        let cube = |lo: i64, hi: i64| Aabb::closed(Vec3i::new(lo, lo, lo), Vec3i::new(hi, hi, hi));
+        let mut set = AabbSet::new();
+        set.insert(cube(10, 12));
+        assert_eq!(set.volume(), 27);
+        set.insert(cube(11, 13));
+        assert_eq!(set.volume(), 27 + 19);
+        set.remove(&cube(9, 11));
+        assert_eq!(set.volume(), 27 + 19 - 8);
+        set.insert(Aabb::closed(Vec3i::new(10, 10, 10), Vec3i::new(10, 10, 10)));
+        assert_eq!(set.volume(), 39);
+        assert!(set.contains(&Vec3i::new(10, 10, 10)));
+        assert!(!set.contains(&Vec3i::new(11, 11, 11)));
+
+        let set: AabbSet<2, i64> = [
+            Aabb::new(Vec2i::new(0, 0), Vec2i::new(4, 4)),
+            Aabb::new(Vec2i::new(2, 2), Vec2i::new(6, 6)),
+            Aabb::new(Vec2i::new(1, 1), Vec2i::new(3, 3)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(set.volume(), 16 + 16 - 4);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::dir::Dir;
+use crate::map2d::Map;
+use crate::vector::Vec2i;
+use std::fmt;
+use std::ops::Index;
+
+const BITS: usize = u64::BITS as usize;
+
+/// Bit-packed 2D boolean map type.
+///
+/// BitMap2d is the bit-packed equivalent of Map<bool>, indexed by Vec2i using "image"
+/// coordinates, i.e. X-east, Y-south. Each row is stored as a sequence of u64 words
+/// where bit x % 64 of word x / 64 holds column x.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitMap2d {
+    pub h: usize,
+    pub w: usize,
+    words_per_row: usize,
+    data: Vec<u64>, // row-major
+}
+
+impl BitMap2d {
+    /// Create map with all bits cleared.
+    pub fn new(h: usize, w: usize) -> Self {
+        let words_per_row = w.div_ceil(BITS);
+        BitMap2d {
+            h,
+            w,
+            words_per_row,
+            data: vec![0; h * words_per_row],
+        }
+    }
+
+    /// Check if coordinate is within map bounds.
+    pub fn contains(&self, p: &Vec2i) -> bool {
+        p.is_in_grid(self.h, self.w)
+    }
+
+    fn word_bit(&self, p: &Vec2i) -> (usize, u64) {
+        let (x, y) = (p.x() as usize, p.y() as usize);
+        (y * self.words_per_row + x / BITS, 1 << (x % BITS))
+    }
+
+    /// Get map element.
+    pub fn get(&self, p: &Vec2i) -> Option<bool> {
+        match self.contains(p) {
+            true => {
+                let (word, bit) = self.word_bit(p);
+                Some(self.data[word] & bit != 0)
+            }
+            false => None,
+        }
+    }
+
+    /// Set map element. Panics if p is outside the map.
+    pub fn set(&mut self, p: &Vec2i, val: bool) {
+        assert!(self.contains(p), "position outside map");
+        let (word, bit) = self.word_bit(p);
+        match val {
+            true => self.data[word] |= bit,
+            false => self.data[word] &= !bit,
+        }
+    }
+
+    /// Set all elements to val.
+    pub fn set_constant(&mut self, val: bool) {
+        self.data.fill(if val { u64::MAX } else { 0 });
+        self.mask_rows();
+    }
+
+    /// Clear bits beyond the map width in the last word of each row.
+    fn mask_rows(&mut self) {
+        let rem = self.w % BITS;
+        if rem != 0 && self.words_per_row != 0 {
+            let mask = (1 << rem) - 1;
+            for row in self.data.chunks_mut(self.words_per_row) {
+                row[self.words_per_row - 1] &= mask;
+            }
+        }
+    }
+
+    /// Number of set elements.
+    pub fn count_ones(&self) -> usize {
+        self.data.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Check if no element is set.
+    pub fn none(&self) -> bool {
+        self.data.iter().all(|w| *w == 0)
+    }
+
+    /// Set all elements that are set in other. Maps must have the same size.
+    pub fn union_with(&mut self, other: &BitMap2d) {
+        assert_eq!((self.h, self.w), (other.h, other.w));
+        self.data
+            .iter_mut()
+            .zip(other.data.iter())
+            .for_each(|(a, b)| *a |= b);
+    }
+
+    /// Clear all elements that are not set in other. Maps must have the same size.
+    pub fn intersect_with(&mut self, other: &BitMap2d) {
+        assert_eq!((self.h, self.w), (other.h, other.w));
+        self.data
+            .iter_mut()
+            .zip(other.data.iter())
+            .for_each(|(a, b)| *a &= b);
+    }
+
+    /// Clear all elements that are set in other. Maps must have the same size.
+    pub fn difference_with(&mut self, other: &BitMap2d) {
+        assert_eq!((self.h, self.w), (other.h, other.w));
+        self.data
+            .iter_mut()
+            .zip(other.data.iter())
+            .for_each(|(a, b)| *a &= !b);
+    }
+
+    /// Return a copy where every element has moved one step in direction dir.
+    ///
+    /// Elements that move outside the map are dropped, and vacated elements are cleared.
+    pub fn shifted(&self, dir: Dir) -> BitMap2d {
+        match dir {
+            Dir::N => self.shifted_rows(1),
+            Dir::S => self.shifted_rows(-1),
+            Dir::E => self.shifted_east(),
+            Dir::W => self.shifted_west(),
+            Dir::NE => self.shifted_rows(1).shifted_east(),
+            Dir::NW => self.shifted_rows(1).shifted_west(),
+            Dir::SE => self.shifted_rows(-1).shifted_east(),
+            Dir::SW => self.shifted_rows(-1).shifted_west(),
+        }
+    }
+
+    /// Move rows so that new row y is old row y + dy.
+    fn shifted_rows(&self, dy: isize) -> BitMap2d {
+        let mut ret = BitMap2d::new(self.h, self.w);
+        let n = self.words_per_row;
+        for y in 0..self.h {
+            if let Some(src) = y.checked_add_signed(dy).filter(|src| *src < self.h) {
+                ret.data[y * n..(y + 1) * n].copy_from_slice(&self.data[src * n..(src + 1) * n]);
+            }
+        }
+        ret
+    }
+
+    /// Move every element one column east (x + 1).
+    fn shifted_east(&self) -> BitMap2d {
+        let mut ret = self.clone();
+        if self.words_per_row == 0 {
+            return ret;
+        }
+        for row in ret.data.chunks_mut(self.words_per_row) {
+            let mut carry = 0;
+            for word in row.iter_mut() {
+                let next_carry = *word >> (BITS - 1);
+                *word = (*word << 1) | carry;
+                carry = next_carry;
+            }
+        }
+        ret.mask_rows();
+        ret
+    }
+
+    /// Move every element one column west (x - 1).
+    fn shifted_west(&self) -> BitMap2d {
+        let mut ret = self.clone();
+        if self.words_per_row == 0 {
+            return ret;
+        }
+        for row in ret.data.chunks_mut(self.words_per_row) {
+            let mut carry = 0;
+            for word in row.iter_mut().rev() {
+                let next_carry = *word << (BITS - 1);
+                *word = (*word >> 1) | carry;
+                carry = next_carry;
+            }
+        }
+        ret
+    }
+
+    /// Iterate over coordinates of set elements, in row-major order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = Vec2i> + '_ {
+        let n = self.words_per_row;
+        self.data.iter().enumerate().flat_map(move |(i, word)| {
+            let (y, x0) = (i / n, (i % n) * BITS);
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let x = x0 + word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(Vec2i::new(x as i64, y as i64))
+            })
+        })
+    }
+
+    /// Convert to Map<bool>.
+    pub fn to_map(&self) -> Map<bool> {
+        let mut ret = Map::new(self.h, self.w);
+        for p in self.iter_ones() {
+            ret[&p] = true;
+        }
+        ret
+    }
+}
+
+impl From<&Map<bool>> for BitMap2d {
+    fn from(map: &Map<bool>) -> Self {
+        let mut ret = BitMap2d::new(map.h, map.w);
+        for (p, _) in map.iter().filter(|(_, v)| **v) {
+            ret.set(&p, true);
+        }
+        ret
+    }
+}
+
+impl fmt::Display for BitMap2d {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_map().render_bool())
+    }
+}
+
+/// Index with Vec2i = (x, y).
+impl Index<&Vec2i> for BitMap2d {
+    type Output = bool;
+    fn index(&self, p: &Vec2i) -> &Self::Output {
+        match self.get(p).expect("position outside map") {
+            true => &true,
+            false => &false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitmap_set_get() {
+        let mut map = BitMap2d::new(3, 70);
+        assert_eq!(map.count_ones(), 0);
+        assert!(map.none());
+
+        map.set(&Vec2i::new(0, 0), true);
+        map.set(&Vec2i::new(65, 1), true);
+        map.set(&Vec2i::new(69, 2), true);
+        assert!(map[&Vec2i::new(65, 1)]);
+        assert!(!map[&Vec2i::new(64, 1)]);
+        assert_eq!(map.get(&Vec2i::new(70, 0)), None);
+        assert_eq!(map.get(&Vec2i::new(0, -1)), None);
+        assert_eq!(map.count_ones(), 3);
+
+        map.set(&Vec2i::new(65, 1), false);
+        assert_eq!(map.count_ones(), 2);
+        assert_eq!(
+            map.iter_ones().collect::<Vec<_>>(),
+            vec![Vec2i::new(0, 0), Vec2i::new(69, 2)]
+        );
+
+        map.set_constant(true);
+        assert_eq!(map.count_ones(), 210);
+    }
+
+    #[test]
+    fn test_bitmap_set_operations() {
+        let a = BitMap2d::from(&Map::from_vecs(vec![vec![true, true, false, false]]));
+        let b = BitMap2d::from(&Map::from_vecs(vec![vec![false, true, true, false]]));
+
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(union.to_string(), "###.");
+
+        let mut intersection = a.clone();
+        intersection.intersect_with(&b);
+        assert_eq!(intersection.to_string(), ".#..");
+
+        let mut difference = a.clone();
+        difference.difference_with(&b);
+        assert_eq!(difference.to_string(), "#...");
+    }
+
+    #[test]
+    fn test_bitmap_shift() {
+        // cross the word boundary at x = 64
+        let mut map = BitMap2d::new(3, 66);
+        map.set(&Vec2i::new(63, 1), true);
+        map.set(&Vec2i::new(65, 1), true);
+
+        let east = map.shifted(Dir::E);
+        assert_eq!(
+            east.iter_ones().collect::<Vec<_>>(),
+            vec![Vec2i::new(64, 1)]
+        );
+
+        let west = map.shifted(Dir::W);
+        assert_eq!(
+            west.iter_ones().collect::<Vec<_>>(),
+            vec![Vec2i::new(62, 1), Vec2i::new(64, 1)]
+        );
+
+        let north = map.shifted(Dir::N);
+        assert_eq!(
+            north.iter_ones().collect::<Vec<_>>(),
+            vec![Vec2i::new(63, 0), Vec2i::new(65, 0)]
+        );
+
+        let south_west = map.shifted(Dir::SW);
+        assert_eq!(
+            south_west.iter_ones().collect::<Vec<_>>(),
+            vec![Vec2i::new(62, 2), Vec2i::new(64, 2)]
+        );
+
+        assert!(map.shifted(Dir::S).shifted(Dir::S).none());
+
+        // maps without columns have no words to shift
+        let empty = BitMap2d::new(3, 0);
+        for dir in [Dir::E, Dir::W, Dir::NE, Dir::SW] {
+            assert!(empty.shifted(dir).none());
+        }
+    }
+
+    #[test]
+    fn test_bitmap_map_conversion() {
+        let map = Map::from_vecs(vec![vec![true, false, true], vec![false, true, false]]);
+        let bits = BitMap2d::from(&map);
+        assert_eq!(bits.count_ones(), 3);
+        assert_eq!(
+            bits.to_map().render_bool().to_string(),
+            map.render_bool().to_string()
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::VecDeque;
+
+/// Marker for a missing child or link.
+const NONE: u32 = u32::MAX;
+
+/// Arena trie over bytes with dense child tables and Aho–Corasick failure links.
+///
+/// Nodes are indices into flat vectors and every node stores one child slot per letter
+/// of the alphabet, so stepping is a single array lookup. After build() has been called,
+/// all dictionary words occurring in a text can be enumerated in one linear pass.
+#[derive(Debug, Clone)]
+pub struct ByteTrie {
+    /// Position of each byte in the alphabet, NONE if not in alphabet.
+    letter: [u32; 256],
+    stride: usize,
+    children: Vec<u32>,
+    depth: Vec<u32>,
+    is_end: Vec<bool>,
+    /// Longest proper suffix of the node that is also in the trie.
+    fail: Vec<u32>,
+    /// Longest proper suffix of the node that is a word.
+    dict: Vec<u32>,
+}
+
+impl Default for ByteTrie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ByteTrie {
+    /// Root node, corresponding to the empty string.
+    pub const ROOT: u32 = 0;
+
+    /// Create an empty trie over all ASCII bytes.
+    pub fn new() -> Self {
+        Self::with_alphabet(&(0..128).collect::<Vec<u8>>())
+    }
+
+    /// Create an empty trie over the given bytes, using one child slot per byte.
+    pub fn with_alphabet(alphabet: &[u8]) -> Self {
+        let mut letter = [NONE; 256];
+        let mut stride = 0;
+        for b in alphabet {
+            if letter[*b as usize] == NONE {
+                letter[*b as usize] = stride as u32;
+                stride += 1;
+            }
+        }
+        ByteTrie {
+            letter,
+            stride,
+            children: vec![NONE; stride],
+            depth: vec![0],
+            is_end: vec![false],
+            fail: Vec::new(),
+            dict: Vec::new(),
+        }
+    }
+
+    /// Create a trie from words and build its failure links.
+    pub fn from_words<'a, I>(alphabet: &[u8], words: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut ret = ByteTrie::with_alphabet(alphabet);
+        for word in words {
+            ret.insert(word.as_bytes());
+        }
+        ret.build();
+        ret
+    }
+
+    /// Number of nodes, including the root.
+    pub fn num_nodes(&self) -> usize {
+        self.depth.len()
+    }
+
+    /// Insert a word. Panics if it contains a byte outside the alphabet.
+    ///
+    /// Invalidates failure links, call build() again before matching.
+    pub fn insert(&mut self, word: &[u8]) {
+        self.fail.clear();
+        self.dict.clear();
+        let mut node = Self::ROOT;
+        for b in word {
+            let slot = self.slot(node, *b).expect("Byte not in alphabet");
+            node = match self.children[slot] {
+                NONE => {
+                    let child = self.num_nodes() as u32;
+                    self.children[slot] = child;
+                    self.children.extend(std::iter::repeat_n(NONE, self.stride));
+                    self.depth.push(self.depth[node as usize] + 1);
+                    self.is_end.push(false);
+                    child
+                }
+                child => child,
+            };
+        }
+        self.is_end[node as usize] = true;
+    }
+
+    fn slot(&self, node: u32, b: u8) -> Option<usize> {
+        match self.letter[b as usize] {
+            NONE => None,
+            l => Some(node as usize * self.stride + l as usize),
+        }
+    }
+
+    /// Child of node along byte b.
+    pub fn step(&self, node: u32, b: u8) -> Option<u32> {
+        let child = self.children[self.slot(node, b)?];
+        (child != NONE).then_some(child)
+    }
+
+    /// Check if a word ends at node.
+    pub fn is_end(&self, node: u32) -> bool {
+        self.is_end[node as usize]
+    }
+
+    /// Length of the string leading to node.
+    pub fn depth(&self, node: u32) -> usize {
+        self.depth[node as usize] as usize
+    }
+
+    /// Check if word is contained in trie.
+    pub fn contains(&self, word: &[u8]) -> bool {
+        self.find_node(word).is_some_and(|node| self.is_end(node))
+    }
+
+    /// Node reached by following prefix from the root.
+    pub fn find_node(&self, prefix: &[u8]) -> Option<u32> {
+        prefix
+            .iter()
+            .try_fold(Self::ROOT, |node, b| self.step(node, *b))
+    }
+
+    pub fn is_built(&self) -> bool {
+        self.fail.len() == self.num_nodes()
+    }
+
+    /// Compute Aho–Corasick failure and dictionary links by breadth-first search.
+    pub fn build(&mut self) {
+        let n = self.num_nodes();
+        self.fail = vec![Self::ROOT; n];
+        self.dict = vec![NONE; n];
+
+        let mut queue = VecDeque::from([Self::ROOT]);
+        while let Some(node) = queue.pop_front() {
+            for l in 0..self.stride {
+                let child = self.children[node as usize * self.stride + l];
+                if child == NONE {
+                    continue;
+                }
+                if node != Self::ROOT {
+                    // longest suffix of node that can be extended by the same letter
+                    let mut f = self.fail[node as usize];
+                    loop {
+                        let next = self.children[f as usize * self.stride + l];
+                        if next != NONE {
+                            self.fail[child as usize] = next;
+                            break;
+                        }
+                        if f == Self::ROOT {
+                            break;
+                        }
+                        f = self.fail[f as usize];
+                    }
+                }
+                let f = self.fail[child as usize];
+                self.dict[child as usize] = match self.is_end(f) {
+                    true => f,
+                    false => self.dict[f as usize],
+                };
+                queue.push_back(child);
+            }
+        }
+    }
+
+    /// Follow failure links until a transition along b exists.
+    fn transition(&self, mut node: u32, b: u8) -> u32 {
+        if self.letter[b as usize] == NONE {
+            return Self::ROOT;
+        }
+        loop {
+            if let Some(next) = self.step(node, b) {
+                return next;
+            }
+            if node == Self::ROOT {
+                return Self::ROOT;
+            }
+            node = self.fail[node as usize];
+        }
+    }
+
+    /// Iterate over all occurrences of words in text as (start, end) byte ranges,
+    /// ordered by end position. Panics if build() has not been called.
+    pub fn find_matches<'a>(&'a self, text: &'a [u8]) -> Matches<'a> {
+        assert!(self.is_built(), "Call build() before matching");
+        Matches {
+            trie: self,
+            text,
+            pos: 0,
+            node: Self::ROOT,
+            pending: NONE,
+        }
+    }
+
+    /// Number of ways text can be split into a sequence of words.
+    pub fn count_segmentations(&self, text: &[u8]) -> u64 {
+        let mut dp = vec![0; text.len() + 1];
+        dp[0] = 1;
+        // matches arrive by increasing end, so dp[start] is final when it is used
+        for (start, end) in self.find_matches(text) {
+            dp[end] += dp[start];
+        }
+        dp[text.len()]
+    }
+}
+
+/// Iterator over word occurrences, see ByteTrie::find_matches.
+pub struct Matches<'a> {
+    trie: &'a ByteTrie,
+    text: &'a [u8],
+    pos: usize,
+    node: u32,
+    /// Next word node to report at the current position.
+    pending: u32,
+}
+
+impl Iterator for Matches<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending == NONE {
+            let b = *self.text.get(self.pos)?;
+            self.pos += 1;
+            self.node = self.trie.transition(self.node, b);
+            self.pending = match self.trie.is_end(self.node) {
+                true => self.node,
+                false => self.trie.dict[self.node as usize],
+            };
+        }
+        let word = self.pending;
+        self.pending = self.trie.dict[word as usize];
+        Some((self.pos - self.trie.depth(word), self.pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_trie() {
+        let mut trie = ByteTrie::with_alphabet(b"ehilos");
+        trie.insert(b"hello");
+        trie.insert(b"helios");
+        trie.insert(b"hel");
+        assert_eq!(trie.num_nodes(), 9);
+        assert!(trie.contains(b"hel"));
+        assert!(trie.contains(b"helios"));
+        assert!(!trie.contains(b"he"));
+        assert!(!trie.contains(b"hellos"));
+        assert!(!trie.contains(b"hex"));
+
+        let node = trie.find_node(b"hel").unwrap();
+        assert_eq!(trie.depth(node), 3);
+        assert!(trie.step(node, b'i').is_some());
+        assert!(trie.step(node, b'x').is_none());
+    }
+
+    #[test]
+    fn test_aho_corasick() {
+        // classic example with overlapping and nested matches
+        let trie = ByteTrie::from_words(b"abcdehirs", ["he", "she", "his", "hers"]);
+        let text = b"ushers";
+        let matches: Vec<_> = trie.find_matches(text).collect();
+        assert_eq!(matches, vec![(1, 4), (2, 4), (2, 6)]);
+        let words: Vec<_> = matches.iter().map(|(s, e)| &text[*s..*e]).collect();
+        assert_eq!(words, vec![&b"she"[..], b"he", b"hers"]);
+
+        // bytes outside the alphabet reset the automaton
+        let matches: Vec<_> = trie.find_matches(b"hixs his").collect();
+        assert_eq!(matches, vec![(5, 8)]);
+    }
+
+    #[test]
+    fn test_aho_corasick_brute_force() {
+        let words = ["a", "ab", "bab", "bc", "bca", "c", "caa"];
+        let trie = ByteTrie::from_words(b"abc", words);
+        let text = b"abccab";
+        let mut expected = Vec::new();
+        for end in 1..=text.len() {
+            for start in 0..end {
+                if words.iter().any(|w| w.as_bytes() == &text[start..end]) {
+                    expected.push((start, end));
+                }
+            }
+        }
+        assert_eq!(trie.find_matches(text).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_count_segmentations() {
+        let trie = ByteTrie::from_words(b"bgruw", ["r", "wr", "b", "g", "bwu", "rb", "gb", "br"]);
+        assert_eq!(trie.count_segmentations(b"brwrr"), 2);
+        assert_eq!(trie.count_segmentations(b"rrbgbr"), 6);
+        assert_eq!(trie.count_segmentations(b"ubwu"), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_match_requires_build() {
+        let mut trie = ByteTrie::new();
+        trie.insert(b"abc");
+        trie.find_matches(b"abc").count();
+    }
+}
@@ -0,0 +1,421 @@
+use crate::hash::{FxHashMap, FxHashMapBuilder};
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+
+/// Boolean gate operation.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Op {
+    And,
+    Or,
+    Xor,
+}
+
+impl Op {
+    pub fn eval(&self, left: bool, rght: bool) -> bool {
+        match self {
+            Op::And => left & rght,
+            Op::Or => left | rght,
+            Op::Xor => left ^ rght,
+        }
+    }
+}
+
+impl std::str::FromStr for Op {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "AND" => Ok(Op::And),
+            "OR" => Ok(Op::Or),
+            "XOR" => Ok(Op::Xor),
+            _ => Err(format!("unknown gate '{s}'")),
+        }
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Op::And => "AND".fmt(f),
+            Op::Or => "OR".fmt(f),
+            Op::Xor => "XOR".fmt(f),
+        }
+    }
+}
+
+/// Two-input gate writing to wire out. Wires are indices into the circuit.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Gate {
+    pub left: usize,
+    pub op: Op,
+    pub rght: usize,
+    pub out: usize,
+}
+
+/// Boolean circuit of named wires connected by gates.
+///
+/// Wires not driven by any gate are inputs. Each wire is driven by at most one gate.
+#[derive(Debug, Clone, Default)]
+pub struct Circuit {
+    names: Vec<String>,
+    ids: FxHashMap<String, usize>,
+    pub gates: Vec<Gate>,
+}
+
+impl Circuit {
+    pub fn new() -> Self {
+        Circuit {
+            names: Vec::new(),
+            ids: FxHashMap::new(),
+            gates: Vec::new(),
+        }
+    }
+
+    /// Parse gates of the form "x00 AND y00 -> z00", one per line.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut ret = Circuit::new();
+        for line in input.lines().filter(|l| !l.trim().is_empty()) {
+            let parts: Vec<_> = line.split_whitespace().collect();
+            let [left, op, rght, "->", out] = parts[..] else {
+                return Err(format!("invalid gate '{line}'"));
+            };
+            ret.add_gate(left, op.parse()?, rght, out);
+        }
+        Ok(ret)
+    }
+
+    /// Id of wire with given name, creating it if it does not exist.
+    pub fn wire(&mut self, name: &str) -> usize {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), self.names.len() - 1);
+        self.names.len() - 1
+    }
+
+    /// Id of existing wire.
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, wire: usize) -> &str {
+        &self.names[wire]
+    }
+
+    pub fn num_wires(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn add_gate(&mut self, left: &str, op: Op, rght: &str, out: &str) {
+        let gate = Gate {
+            left: self.wire(left),
+            op,
+            rght: self.wire(rght),
+            out: self.wire(out),
+        };
+        self.gates.push(gate);
+    }
+
+    /// Gate driving each wire, None for inputs.
+    pub fn drivers(&self) -> Vec<Option<usize>> {
+        let mut ret = vec![None; self.num_wires()];
+        for (i, gate) in self.gates.iter().enumerate() {
+            ret[gate.out] = Some(i);
+        }
+        ret
+    }
+
+    /// Gates reading each wire.
+    pub fn consumers(&self) -> Vec<Vec<usize>> {
+        let mut ret = vec![Vec::new(); self.num_wires()];
+        for (i, gate) in self.gates.iter().enumerate() {
+            ret[gate.left].push(i);
+            if gate.rght != gate.left {
+                ret[gate.rght].push(i);
+            }
+        }
+        ret
+    }
+
+    /// Gates ordered so that every gate comes after the gates driving its inputs.
+    ///
+    /// Returns None if the circuit contains a cycle.
+    pub fn topological_order(&self) -> Option<Vec<usize>> {
+        let drivers = self.drivers();
+        let consumers = self.consumers();
+        let mut missing: Vec<usize> = self
+            .gates
+            .iter()
+            .map(|g| {
+                let mut inputs = vec![g.left, g.rght];
+                inputs.dedup();
+                inputs.iter().filter(|w| drivers[**w].is_some()).count()
+            })
+            .collect();
+
+        let mut queue: VecDeque<usize> =
+            (0..self.gates.len()).filter(|i| missing[*i] == 0).collect();
+        let mut ret = Vec::with_capacity(self.gates.len());
+        while let Some(i) = queue.pop_front() {
+            ret.push(i);
+            for j in consumers[self.gates[i].out].iter() {
+                missing[*j] -= 1;
+                if missing[*j] == 0 {
+                    queue.push_back(*j);
+                }
+            }
+        }
+        (ret.len() == self.gates.len()).then_some(ret)
+    }
+
+    /// Evaluate all wires given input values; wires depending on unset inputs are None.
+    ///
+    /// Returns None if the circuit contains a cycle.
+    pub fn evaluate<I>(&self, inputs: I) -> Option<Vec<Option<bool>>>
+    where
+        I: IntoIterator<Item = (usize, bool)>,
+    {
+        let mut values = vec![None; self.num_wires()];
+        for (wire, val) in inputs {
+            values[wire] = Some(val);
+        }
+        for i in self.topological_order()? {
+            let Gate {
+                left,
+                op,
+                rght,
+                out,
+            } = self.gates[i];
+            if let (Some(l), Some(r)) = (values[left], values[rght]) {
+                values[out] = Some(op.eval(l, r));
+            }
+        }
+        Some(values)
+    }
+
+    /// Wires named prefix followed by a number, ordered by number (least significant first).
+    pub fn bus(&self, prefix: char) -> Vec<usize> {
+        let mut ret: Vec<(u32, usize)> = self
+            .names
+            .iter()
+            .enumerate()
+            .filter_map(|(id, name)| {
+                let idx = name.strip_prefix(prefix)?.parse().ok()?;
+                Some((idx, id))
+            })
+            .collect();
+        ret.sort();
+        ret.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Inputs setting the bus with given prefix to the bits of val.
+    pub fn bus_inputs(&self, prefix: char, val: u64) -> Vec<(usize, bool)> {
+        self.bus(prefix)
+            .into_iter()
+            .enumerate()
+            .map(|(i, w)| (w, (val >> i) & 1 == 1))
+            .collect()
+    }
+
+    /// Read the bus with given prefix as a number, None if any of its wires is unset.
+    pub fn read_bus(&self, values: &[Option<bool>], prefix: char) -> Option<u64> {
+        self.bus(prefix)
+            .iter()
+            .enumerate()
+            .try_fold(0, |acc, (i, w)| Some(acc | (values[*w]? as u64) << i))
+    }
+
+    /// Swap the gates driving two wires.
+    pub fn swap_outputs(&mut self, w1: usize, w2: usize) {
+        for gate in self.gates.iter_mut() {
+            if gate.out == w1 {
+                gate.out = w2;
+            } else if gate.out == w2 {
+                gate.out = w1;
+            }
+        }
+    }
+
+    /// Export as Graphviz DOT, with wires as ellipses and gates as boxes.
+    pub fn to_dot(&self) -> String {
+        let mut ret = String::from("digraph circuit {\n");
+        for (i, gate) in self.gates.iter().enumerate() {
+            let g = format!("g{i}");
+            writeln!(ret, "  {g} [shape=box, label=\"{}\"];", gate.op).unwrap();
+            writeln!(ret, "  {} -> {g};", self.name(gate.left)).unwrap();
+            writeln!(ret, "  {} -> {g};", self.name(gate.rght)).unwrap();
+            writeln!(ret, "  {g} -> {};", self.name(gate.out)).unwrap();
+        }
+        ret.push('}');
+        ret
+    }
+
+    /// Wires that violate the structure of a ripple-carry adder z = x + y.
+    ///
+    /// Bit i (i > 0) of a ripple-carry adder is built as
+    ///   s_i = x_i XOR y_i,  z_i = s_i XOR c_i,
+    ///   c_{i+1} = (x_i AND y_i) OR (s_i AND c_i),
+    /// with a half adder for bit 0 and the last carry as the highest z wire.
+    /// Gate outputs breaking these rules are returned; for an adder with swapped
+    /// outputs these are exactly the swapped wires.
+    pub fn adder_violations(&self) -> Vec<usize> {
+        let consumers = self.consumers();
+        let z_bus = self.bus('z');
+        let last_z = z_bus.last().copied();
+        let mut input = vec![false; self.num_wires()];
+        let mut output = vec![false; self.num_wires()];
+        self.bus('x')
+            .into_iter()
+            .chain(self.bus('y'))
+            .for_each(|w| input[w] = true);
+        z_bus.into_iter().for_each(|w| output[w] = true);
+        let is_input = |w: usize| input[w];
+        let is_z = |w: usize| output[w];
+        let is_bit0 = |g: &Gate| {
+            let names = [self.name(g.left), self.name(g.rght)];
+            names.contains(&"x00") && names.contains(&"y00")
+        };
+        let feeds = |w: usize, op: Op| consumers[w].iter().any(|i| self.gates[*i].op == op);
+
+        let mut ret: Vec<usize> = self
+            .gates
+            .iter()
+            .filter(|g| {
+                let from_inputs = is_input(g.left) && is_input(g.rght);
+                match g.op {
+                    // the final carry is the only output not computed by XOR
+                    _ if is_z(g.out) && g.op != Op::Xor => Some(g.out) != last_z,
+                    // XOR gates either combine inputs or produce an output bit
+                    Op::Xor if !from_inputs => !is_z(g.out),
+                    // x_i XOR y_i is a sum input to another XOR (except for z00)
+                    Op::Xor => !is_bit0(g) && !feeds(g.out, Op::Xor),
+                    // carries are collected by OR (except the first carry)
+                    Op::And => !is_bit0(g) && !feeds(g.out, Op::Or),
+                    Op::Or => false,
+                }
+            })
+            .map(|g| g.out)
+            .collect();
+        ret.sort();
+        ret.dedup();
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ripple-carry adder for n-bit numbers.
+    fn adder(n: usize) -> Circuit {
+        adder_named(n, ["s", "a", "b", "c"])
+    }
+
+    /// Ripple-carry adder with the given name prefixes for the sum, both carry terms and
+    /// the carry wires.
+    fn adder_named(n: usize, [ps, pa, pb, pc]: [&str; 4]) -> Circuit {
+        let mut c = Circuit::new();
+        c.add_gate("x00", Op::Xor, "y00", "z00");
+        c.add_gate("x00", Op::And, "y00", &format!("{pc}01"));
+        for i in 1..n {
+            let (x, y) = (format!("x{i:02}"), format!("y{i:02}"));
+            let (s, a, b) = (
+                format!("{ps}{i:02}"),
+                format!("{pa}{i:02}"),
+                format!("{pb}{i:02}"),
+            );
+            let carry = format!("{pc}{i:02}");
+            let next = match i + 1 {
+                j if j == n => format!("z{j:02}"),
+                j => format!("{pc}{j:02}"),
+            };
+            c.add_gate(&x, Op::Xor, &y, &s);
+            c.add_gate(&s, Op::Xor, &carry, &format!("z{i:02}"));
+            c.add_gate(&x, Op::And, &y, &a);
+            c.add_gate(&s, Op::And, &carry, &b);
+            c.add_gate(&a, Op::Or, &b, &next);
+        }
+        c
+    }
+
+    fn add(c: &Circuit, x: u64, y: u64) -> Option<u64> {
+        let inputs = c.bus_inputs('x', x).into_iter().chain(c.bus_inputs('y', y));
+        c.read_bus(&c.evaluate(inputs)?, 'z')
+    }
+
+    #[test]
+    fn test_parse_and_evaluate() {
+        let c =
+            Circuit::parse("x00 AND y00 -> z00\nx01 XOR y01 -> z01\nx02 OR y02 -> z02\n").unwrap();
+        assert_eq!(c.gates.len(), 3);
+        let inputs = c
+            .bus_inputs('x', 0b111)
+            .into_iter()
+            .chain(c.bus_inputs('y', 0b010));
+        let values = c.evaluate(inputs).unwrap();
+        assert_eq!(c.read_bus(&values, 'z'), Some(0b100));
+        assert!(Circuit::parse("x00 NAND y00 -> z00").is_err());
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let mut c = Circuit::parse("b AND x -> c\na OR y -> b").unwrap();
+        assert_eq!(c.topological_order(), Some(vec![1, 0]));
+        c.add_gate("c", Op::Xor, "z", "a");
+        assert_eq!(c.topological_order(), None);
+    }
+
+    #[test]
+    fn test_adder() {
+        let c = adder(6);
+        assert_eq!(add(&c, 27, 45), Some(72));
+        assert_eq!(add(&c, 63, 63), Some(126));
+        assert!(c.adder_violations().is_empty());
+    }
+
+    #[test]
+    fn test_adder_violations() {
+        let mut c = adder(6);
+        let swaps = [("z03", "c04"), ("s02", "a02")];
+        for (w1, w2) in swaps {
+            c.swap_outputs(c.id(w1).unwrap(), c.id(w2).unwrap());
+        }
+        assert_ne!(add(&c, 27, 45), Some(72));
+
+        let mut found: Vec<_> = c
+            .adder_violations()
+            .into_iter()
+            .map(|w| c.name(w))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec!["a02", "c04", "s02", "z03"]);
+    }
+
+    #[test]
+    fn test_adder_violations_wire_names() {
+        // intermediate wires whose names start with x or z are not bus wires
+        let mut c = adder_named(6, ["xs", "za", "b", "zc"]);
+        assert_eq!(add(&c, 27, 45), Some(72));
+        assert_eq!(c.bus('z').len(), 7);
+        assert!(c.adder_violations().is_empty());
+
+        c.swap_outputs(c.id("xs02").unwrap(), c.id("za02").unwrap());
+        let mut found: Vec<_> = c
+            .adder_violations()
+            .into_iter()
+            .map(|w| c.name(w))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec!["xs02", "za02"]);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let c = Circuit::parse("x00 AND y00 -> z00").unwrap();
+        assert_eq!(
+            c.to_dot(),
+            "digraph circuit {\n  g0 [shape=box, label=\"AND\"];\n  x00 -> g0;\n  y00 -> g0;\n  g0 -> z00;\n}"
+        );
+    }
+}
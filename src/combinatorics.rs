@@ -0,0 +1,441 @@
+/// Iterator that lends each item as a borrowed slice into an internal buffer.
+///
+/// Unlike std::iter::Iterator no allocation is needed per item, but an item must be
+/// dropped before the next one is requested:
+///
+/// ```text
+/// while let Some(c) = it.next() { ... }
+/// ```
+pub trait LendingIterator {
+    type Item: ?Sized;
+
+    fn next(&mut self) -> Option<&Self::Item>;
+
+    /// Number of remaining items.
+    fn count(mut self) -> usize
+    where
+        Self: Sized,
+    {
+        let mut ret = 0;
+        while self.next().is_some() {
+            ret += 1;
+        }
+        ret
+    }
+}
+
+/// Collect all items of a slice-lending iterator, mainly useful for testing.
+pub fn collect_vecs<T: Clone, I: LendingIterator<Item = [T]>>(mut it: I) -> Vec<Vec<T>> {
+    let mut ret = Vec::new();
+    while let Some(x) = it.next() {
+        ret.push(x.to_vec());
+    }
+    ret
+}
+
+/// k-combinations of 0..n as increasing index slices, in lexicographic order.
+pub struct Combinations {
+    n: usize,
+    idx: Vec<usize>,
+    first: bool,
+}
+
+impl Combinations {
+    pub fn new(n: usize, k: usize) -> Self {
+        Combinations {
+            n,
+            idx: (0..k).collect(),
+            first: k <= n,
+        }
+    }
+}
+
+impl LendingIterator for Combinations {
+    type Item = [usize];
+
+    fn next(&mut self) -> Option<&[usize]> {
+        if std::mem::take(&mut self.first) {
+            return Some(&self.idx);
+        }
+        let k = self.idx.len();
+        // rightmost index that is not at its maximum value n - k + i
+        let i = (0..k).rev().find(|i| self.idx[*i] + k < self.n + i)?;
+        self.idx[i] += 1;
+        for j in i + 1..k {
+            self.idx[j] = self.idx[j - 1] + 1;
+        }
+        Some(&self.idx)
+    }
+}
+
+/// Non-decreasing k-tuples of 0..n (multisets of size k), in lexicographic order.
+pub struct CombinationsWithReplacement {
+    n: usize,
+    idx: Vec<usize>,
+    first: bool,
+}
+
+impl CombinationsWithReplacement {
+    pub fn new(n: usize, k: usize) -> Self {
+        CombinationsWithReplacement {
+            n,
+            idx: vec![0; k],
+            first: n > 0 || k == 0,
+        }
+    }
+}
+
+impl LendingIterator for CombinationsWithReplacement {
+    type Item = [usize];
+
+    fn next(&mut self) -> Option<&[usize]> {
+        if std::mem::take(&mut self.first) {
+            return Some(&self.idx);
+        }
+        let i = self.idx.iter().rposition(|x| *x + 1 < self.n)?;
+        let v = self.idx[i] + 1;
+        self.idx[i..].iter_mut().for_each(|x| *x = v);
+        Some(&self.idx)
+    }
+}
+
+/// All k-tuples of 0..n, in lexicographic order (the last element changes fastest).
+pub struct CartesianPower {
+    n: usize,
+    idx: Vec<usize>,
+    first: bool,
+}
+
+impl CartesianPower {
+    pub fn new(n: usize, k: usize) -> Self {
+        CartesianPower {
+            n,
+            idx: vec![0; k],
+            first: n > 0 || k == 0,
+        }
+    }
+}
+
+impl LendingIterator for CartesianPower {
+    type Item = [usize];
+
+    fn next(&mut self) -> Option<&[usize]> {
+        if std::mem::take(&mut self.first) {
+            return Some(&self.idx);
+        }
+        let i = self.idx.iter().rposition(|x| *x + 1 < self.n)?;
+        self.idx[i] += 1;
+        self.idx[i + 1..].iter_mut().for_each(|x| *x = 0);
+        Some(&self.idx)
+    }
+}
+
+/// Permutations of items generated by Heap's algorithm, one swap per step.
+///
+/// The order is not lexicographic; the first item is the input order.
+pub struct Permutations<T> {
+    items: Vec<T>,
+    c: Vec<usize>,
+    i: usize,
+    first: bool,
+}
+
+impl<T> Permutations<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Permutations {
+            c: vec![0; items.len()],
+            items,
+            i: 1,
+            first: true,
+        }
+    }
+}
+
+impl<T> LendingIterator for Permutations<T> {
+    type Item = [T];
+
+    fn next(&mut self) -> Option<&[T]> {
+        if std::mem::take(&mut self.first) {
+            return Some(&self.items);
+        }
+        while self.i < self.items.len() {
+            if self.c[self.i] < self.i {
+                let j = if self.i.is_multiple_of(2) {
+                    0
+                } else {
+                    self.c[self.i]
+                };
+                self.items.swap(j, self.i);
+                self.c[self.i] += 1;
+                self.i = 1;
+                return Some(&self.items);
+            }
+            self.c[self.i] = 0;
+            self.i += 1;
+        }
+        None
+    }
+}
+
+/// Ways to write n as an ordered sum of k non-negative parts, in lexicographic order.
+pub struct Compositions {
+    n: usize,
+    parts: Vec<usize>,
+    first: bool,
+}
+
+impl Compositions {
+    pub fn new(n: usize, k: usize) -> Self {
+        let mut parts = vec![0; k];
+        if let Some(last) = parts.last_mut() {
+            *last = n;
+        }
+        Compositions {
+            n,
+            parts,
+            first: k > 0 || n == 0,
+        }
+    }
+}
+
+impl LendingIterator for Compositions {
+    type Item = [usize];
+
+    fn next(&mut self) -> Option<&[usize]> {
+        if std::mem::take(&mut self.first) {
+            return Some(&self.parts);
+        }
+        let k = self.parts.len();
+        if k < 2 || self.parts[0] == self.n {
+            return None;
+        }
+        // move one unit from the tail to the rightmost part that can grow,
+        // and collect the rest of the tail in the last part
+        let j = (0..k - 1)
+            .rev()
+            .find(|j| self.parts[j + 1..].iter().any(|x| *x > 0))?;
+        let rest = self.parts[j + 1..].iter().sum::<usize>() - 1;
+        self.parts[j] += 1;
+        self.parts[j + 1..].iter_mut().for_each(|x| *x = 0);
+        self.parts[k - 1] = rest;
+        Some(&self.parts)
+    }
+}
+
+/// Partitions of n into non-increasing positive parts, in reverse lexicographic order
+/// starting from [n].
+pub struct Partitions {
+    parts: Vec<usize>,
+    first: bool,
+}
+
+impl Partitions {
+    pub fn new(n: usize) -> Self {
+        Partitions {
+            parts: if n > 0 { vec![n] } else { vec![] },
+            first: true,
+        }
+    }
+}
+
+impl LendingIterator for Partitions {
+    type Item = [usize];
+
+    fn next(&mut self) -> Option<&[usize]> {
+        if std::mem::take(&mut self.first) {
+            return Some(&self.parts);
+        }
+        // remove trailing ones, decrease the last part > 1 and refill greedily
+        let ones = self.parts.iter().rev().take_while(|x| **x == 1).count();
+        self.parts.truncate(self.parts.len() - ones);
+        let last = self.parts.last_mut()?;
+        *last -= 1;
+        let size = *last;
+        let mut rest = ones + 1;
+        while rest > 0 {
+            self.parts.push(size.min(rest));
+            rest -= size.min(rest);
+        }
+        Some(&self.parts)
+    }
+}
+
+/// Binomial coefficient n choose k, None on overflow.
+pub fn binomial(n: u64, k: u64) -> Option<u64> {
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k);
+    let mut ret: u64 = 1;
+    for i in 0..k {
+        // ret * (n - i) is divisible by i + 1 since ret = C(n, i)
+        let next = ret as u128 * (n - i) as u128 / (i + 1) as u128;
+        ret = u64::try_from(next).ok()?;
+    }
+    Some(ret)
+}
+
+/// Multinomial coefficient (k1 + ... + km)! / (k1! ... km!), None on overflow.
+pub fn multinomial(ks: &[u64]) -> Option<u64> {
+    let mut n: u64 = 0;
+    let mut ret: u64 = 1;
+    for k in ks {
+        n = n.checked_add(*k)?;
+        ret = ret.checked_mul(binomial(n, *k)?)?;
+    }
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::NChooseK;
+    use std::time::Instant;
+
+    #[test]
+    fn test_combinations() {
+        let all = collect_vecs(Combinations::new(4, 2));
+        assert_eq!(
+            all,
+            vec![
+                vec![0, 1],
+                vec![0, 2],
+                vec![0, 3],
+                vec![1, 2],
+                vec![1, 3],
+                vec![2, 3]
+            ]
+        );
+        assert_eq!(all, NChooseK::new(4, 2).collect::<Vec<_>>());
+        assert_eq!(Combinations::new(4, 0).count(), 1);
+        assert_eq!(Combinations::new(4, 4).count(), 1);
+        assert_eq!(Combinations::new(3, 4).count(), 0);
+        assert_eq!(Combinations::new(10, 4).count(), 210);
+    }
+
+    #[test]
+    fn test_combinations_with_replacement() {
+        let all = collect_vecs(CombinationsWithReplacement::new(3, 2));
+        assert_eq!(
+            all,
+            vec![
+                vec![0, 0],
+                vec![0, 1],
+                vec![0, 2],
+                vec![1, 1],
+                vec![1, 2],
+                vec![2, 2]
+            ]
+        );
+        assert_eq!(CombinationsWithReplacement::new(5, 3).count(), 35);
+        assert_eq!(CombinationsWithReplacement::new(0, 2).count(), 0);
+    }
+
+    #[test]
+    fn test_cartesian_power() {
+        let all = collect_vecs(CartesianPower::new(2, 3));
+        assert_eq!(all.len(), 8);
+        assert_eq!(all[0], vec![0, 0, 0]);
+        assert_eq!(all[1], vec![0, 0, 1]);
+        assert_eq!(all[7], vec![1, 1, 1]);
+        assert_eq!(CartesianPower::new(3, 0).count(), 1);
+    }
+
+    #[test]
+    fn test_permutations() {
+        let mut all = collect_vecs(Permutations::new(vec!['a', 'b', 'c']));
+        assert_eq!(all[0], vec!['a', 'b', 'c']);
+        assert_eq!(all.len(), 6);
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 6);
+        assert_eq!(Permutations::new(vec![1, 2, 3, 4, 5]).count(), 120);
+        assert_eq!(Permutations::<u8>::new(vec![]).count(), 1);
+    }
+
+    #[test]
+    fn test_compositions() {
+        let all = collect_vecs(Compositions::new(2, 3));
+        assert_eq!(
+            all,
+            vec![
+                vec![0, 0, 2],
+                vec![0, 1, 1],
+                vec![0, 2, 0],
+                vec![1, 0, 1],
+                vec![1, 1, 0],
+                vec![2, 0, 0]
+            ]
+        );
+        // stars and bars: C(n + k - 1, k - 1)
+        assert_eq!(Compositions::new(100, 4).count(), 176851);
+        assert_eq!(Compositions::new(5, 1).count(), 1);
+        assert_eq!(Compositions::new(5, 0).count(), 0);
+        assert_eq!(Compositions::new(0, 0).count(), 1);
+    }
+
+    #[test]
+    fn test_partitions() {
+        let all = collect_vecs(Partitions::new(5));
+        assert_eq!(
+            all,
+            vec![
+                vec![5],
+                vec![4, 1],
+                vec![3, 2],
+                vec![3, 1, 1],
+                vec![2, 2, 1],
+                vec![2, 1, 1, 1],
+                vec![1, 1, 1, 1, 1]
+            ]
+        );
+        assert_eq!(Partitions::new(20).count(), 627);
+        assert_eq!(Partitions::new(0).count(), 1);
+    }
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(binomial(5, 2), Some(10));
+        assert_eq!(binomial(5, 6), Some(0));
+        assert_eq!(binomial(0, 0), Some(1));
+        assert_eq!(binomial(67, 33), Some(14226520737620288370));
+        assert_eq!(binomial(68, 34), None);
+        assert_eq!(binomial(1 << 40, 1), Some(1 << 40));
+    }
+
+    #[test]
+    fn test_multinomial() {
+        assert_eq!(multinomial(&[2, 1, 1]), Some(12));
+        assert_eq!(multinomial(&[]), Some(1));
+        assert_eq!(multinomial(&[3]), Some(1));
+        assert_eq!(multinomial(&[5; 4]), Some(11732745024));
+        assert_eq!(multinomial(&[10, 10, 10]), Some(5550996791340));
+        assert_eq!(multinomial(&[10; 5]), None);
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_combinations_vs_nchoosek() {
+        // run with: cargo test --release bench_combinations_vs_nchoosek -- --ignored --nocapture
+        let (n, k) = (30, 6);
+
+        let t0 = Instant::now();
+        let mut sum_nchoosek = 0;
+        for c in NChooseK::new(n, k) {
+            sum_nchoosek += c[k - 1];
+        }
+        let dt_nchoosek = t0.elapsed();
+
+        let t0 = Instant::now();
+        let mut sum = 0;
+        let mut it = Combinations::new(n, k);
+        while let Some(c) = it.next() {
+            sum += c[k - 1];
+        }
+        let dt = t0.elapsed();
+
+        assert_eq!(sum, sum_nchoosek);
+        println!("NChooseK:     {dt_nchoosek:?}");
+        println!("Combinations: {dt:?}");
+    }
+}
@@ -0,0 +1,213 @@
+use crate::map2d::Map;
+use crate::vector::Vec2i;
+
+/// Coordinate compression along one axis.
+///
+/// Given sorted unique coordinates c[0] < c[1] < ... < c[k], the axis is split into cells:
+///  - cell 2i covers exactly coordinate c[i] (size 1),
+///  - cell 2i + 1 covers the open gap (c[i], c[i+1]) (size c[i+1] - c[i] - 1, possibly 0).
+///
+/// Summing cell sizes over a range of cells therefore counts integer coordinates exactly.
+#[derive(Debug, Clone)]
+pub struct CompressedAxis {
+    coords: Vec<i64>,
+}
+
+impl CompressedAxis {
+    /// Create from (unsorted, possibly duplicate) coordinates.
+    pub fn new<I: IntoIterator<Item = i64>>(coords: I) -> Self {
+        let mut coords: Vec<i64> = coords.into_iter().collect();
+        coords.sort_unstable();
+        coords.dedup();
+        CompressedAxis { coords }
+    }
+
+    /// Number of cells.
+    pub fn len(&self) -> usize {
+        (2 * self.coords.len()).saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.coords.is_empty()
+    }
+
+    /// Cell containing coordinate x, if x is within the compressed range.
+    pub fn cell(&self, x: i64) -> Option<usize> {
+        match self.coords.binary_search(&x) {
+            Ok(i) => Some(2 * i),
+            Err(i) if i > 0 && i < self.coords.len() => Some(2 * i - 1),
+            Err(_) => None,
+        }
+    }
+
+    /// First real coordinate covered by cell.
+    pub fn start(&self, cell: usize) -> i64 {
+        match cell % 2 {
+            0 => self.coords[cell / 2],
+            _ => self.coords[cell / 2] + 1,
+        }
+    }
+
+    /// Number of integer coordinates covered by cell.
+    pub fn size(&self, cell: usize) -> i64 {
+        match cell % 2 {
+            0 => 1,
+            _ => self.coords[cell / 2 + 1] - self.coords[cell / 2] - 1,
+        }
+    }
+}
+
+/// 2D coordinate compression of a set of points onto a compact Map index space.
+///
+/// Each axis is compressed independently with CompressedAxis, so every compressed cell
+/// is a rectangle of real lattice points with known width and height.
+#[derive(Debug, Clone)]
+pub struct CoordCompression {
+    pub xs: CompressedAxis,
+    pub ys: CompressedAxis,
+}
+
+impl CoordCompression {
+    /// Create compression containing all given points.
+    pub fn new<I: IntoIterator<Item = Vec2i>>(points: I) -> Self {
+        let (xs, ys): (Vec<_>, Vec<_>) = points.into_iter().map(|p| (p.x(), p.y())).unzip();
+        CoordCompression {
+            xs: CompressedAxis::new(xs),
+            ys: CompressedAxis::new(ys),
+        }
+    }
+
+    /// Height of the compressed grid.
+    pub fn h(&self) -> usize {
+        self.ys.len()
+    }
+
+    /// Width of the compressed grid.
+    pub fn w(&self) -> usize {
+        self.xs.len()
+    }
+
+    /// Create compressed map filled with constant.
+    pub fn new_map<T: Clone>(&self, t: T) -> Map<T> {
+        Map::new_constant(self.h(), self.w(), t)
+    }
+
+    /// Compressed cell containing real point p.
+    pub fn to_compressed(&self, p: &Vec2i) -> Option<Vec2i> {
+        let x = self.xs.cell(p.x())?;
+        let y = self.ys.cell(p.y())?;
+        Some(Vec2i::new(x as i64, y as i64))
+    }
+
+    /// Real point at the min corner of compressed cell c.
+    pub fn to_real(&self, c: &Vec2i) -> Vec2i {
+        Vec2i::new(self.xs.start(c.x() as usize), self.ys.start(c.y() as usize))
+    }
+
+    /// Real (width, height) of compressed cell c.
+    pub fn cell_size(&self, c: &Vec2i) -> (i64, i64) {
+        (self.xs.size(c.x() as usize), self.ys.size(c.y() as usize))
+    }
+
+    /// Number of real lattice points in compressed cell c.
+    pub fn cell_area(&self, c: &Vec2i) -> i64 {
+        let (w, h) = self.cell_size(c);
+        w * h
+    }
+
+    /// Number of real lattice points covered by compressed cells where f returns true.
+    pub fn area<T, F>(&self, map: &Map<T>, f: F) -> i64
+    where
+        F: Fn(&T) -> bool,
+    {
+        map.iter()
+            .filter(|(_, t)| f(t))
+            .map(|(c, _)| self.cell_area(&c))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dir::DIRECTIONS;
+    use crate::geometry::Polygon;
+
+    #[test]
+    fn test_compressed_axis() {
+        let axis = CompressedAxis::new([10, 3, 4, 10, 20]);
+        assert_eq!(axis.len(), 7);
+        assert_eq!(axis.cell(3), Some(0));
+        assert_eq!(axis.cell(4), Some(2));
+        assert_eq!(axis.cell(5), Some(3));
+        assert_eq!(axis.cell(20), Some(6));
+        assert_eq!(axis.cell(2), None);
+        assert_eq!(axis.cell(21), None);
+
+        let sizes: Vec<_> = (0..axis.len()).map(|c| axis.size(c)).collect();
+        assert_eq!(sizes, vec![1, 0, 1, 5, 1, 9, 1]);
+        assert_eq!(sizes.iter().sum::<i64>(), 20 - 3 + 1);
+        assert_eq!(axis.start(3), 5);
+        assert_eq!(axis.start(4), 10);
+    }
+
+    #[test]
+    fn test_compressed_polygon_area() {
+        // example from 2025 day 9
+        let corners: Vec<_> = [
+            (7, 1),
+            (11, 1),
+            (11, 7),
+            (9, 7),
+            (9, 5),
+            (2, 5),
+            (2, 3),
+            (7, 3),
+        ]
+        .into_iter()
+        .map(|(x, y)| Vec2i::new(x, y))
+        .collect();
+        let comp = CoordCompression::new(corners.iter().copied());
+        assert_eq!((comp.h(), comp.w()), (7, 7));
+
+        // draw boundary on compressed grid
+        let mut map = comp.new_map('.');
+        for (a, b) in corners.iter().zip(corners.iter().cycle().skip(1)) {
+            let (ca, cb) = (
+                comp.to_compressed(a).unwrap(),
+                comp.to_compressed(b).unwrap(),
+            );
+            let d = cb - ca;
+            let step = Vec2i::new(d.x().signum(), d.y().signum());
+            let mut c = ca;
+            while c != cb {
+                map[&c] = '#';
+                c += step;
+            }
+        }
+
+        // flood fill outside from all edge cells
+        let mut stack: Vec<_> = map
+            .iter()
+            .filter(|(p, t)| {
+                **t == '.'
+                    && (p.x() == 0
+                        || p.y() == 0
+                        || p.x() == comp.w() as i64 - 1
+                        || p.y() == comp.h() as i64 - 1)
+            })
+            .map(|(p, _)| p)
+            .collect();
+        while let Some(p) = stack.pop() {
+            if map[&p] != '.' {
+                continue;
+            }
+            map[&p] = 'o';
+            stack.extend(DIRECTIONS.iter().filter_map(|d| map.step_within(&p, *d, 1)));
+        }
+
+        let area = comp.area(&map, |t| *t != 'o');
+        assert_eq!(area, Polygon::new(corners).lattice_points());
+        assert_eq!(comp.to_real(&Vec2i::new(1, 1)), Vec2i::new(3, 2));
+    }
+}
@@ -37,6 +37,17 @@ impl Dir {
         }
     }
 
+    /// Arrow character of a cardinal direction, inverse of from_char.
+    pub fn to_char(&self) -> Option<char> {
+        match self {
+            Dir::N => Some('^'),
+            Dir::E => Some('>'),
+            Dir::S => Some('v'),
+            Dir::W => Some('<'),
+            _ => None,
+        }
+    }
+
     pub fn turn_right(&self) -> Dir {
         match self {
             Dir::N => Dir::E,
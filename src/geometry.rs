@@ -0,0 +1,452 @@
+use crate::math::gcd;
+use crate::vector::Vec2i;
+use std::cmp::{max, min};
+
+/// Closed line segment between two lattice points.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Segment {
+    pub p0: Vec2i,
+    pub p1: Vec2i,
+}
+
+impl Segment {
+    pub fn new(p0: Vec2i, p1: Vec2i) -> Self {
+        Segment { p0, p1 }
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.p0.x() == self.p1.x()
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.p0.y() == self.p1.y()
+    }
+
+    pub fn xmin(&self) -> i64 {
+        min(self.p0.x(), self.p1.x())
+    }
+
+    pub fn xmax(&self) -> i64 {
+        max(self.p0.x(), self.p1.x())
+    }
+
+    pub fn ymin(&self) -> i64 {
+        min(self.p0.y(), self.p1.y())
+    }
+
+    pub fn ymax(&self) -> i64 {
+        max(self.p0.y(), self.p1.y())
+    }
+
+    /// Number of lattice points on the segment, including both end points.
+    pub fn lattice_points(&self) -> u64 {
+        let d = self.p1 - self.p0;
+        gcd(d.x().unsigned_abs(), d.y().unsigned_abs()) + 1
+    }
+
+    /// Check if p lies on the segment.
+    pub fn contains(&self, p: &Vec2i) -> bool {
+        (self.p1 - self.p0).cross(*p - self.p0) == 0
+            && (self.xmin()..=self.xmax()).contains(&p.x())
+            && (self.ymin()..=self.ymax()).contains(&p.y())
+    }
+
+    /// Check if two segments share at least one point.
+    pub fn intersects(&self, other: &Segment) -> bool {
+        let o1 = orientation(self.p0, self.p1, other.p0);
+        let o2 = orientation(self.p0, self.p1, other.p1);
+        let o3 = orientation(other.p0, other.p1, self.p0);
+        let o4 = orientation(other.p0, other.p1, self.p1);
+
+        if o1 * o2 < 0 && o3 * o4 < 0 {
+            return true; // proper crossing
+        }
+
+        self.contains(&other.p0)
+            || self.contains(&other.p1)
+            || other.contains(&self.p0)
+            || other.contains(&self.p1)
+    }
+
+    /// Intersection of two axis-aligned segments.
+    ///
+    /// Returns the shared part as a (possibly single-point) segment.
+    pub fn intersection(&self, other: &Segment) -> Option<Segment> {
+        debug_assert!(self.is_vertical() || self.is_horizontal());
+        debug_assert!(other.is_vertical() || other.is_horizontal());
+        let xmin = max(self.xmin(), other.xmin());
+        let xmax = min(self.xmax(), other.xmax());
+        let ymin = max(self.ymin(), other.ymin());
+        let ymax = min(self.ymax(), other.ymax());
+        if xmin > xmax || ymin > ymax {
+            return None;
+        }
+        Some(Segment::new(Vec2i::new(xmin, ymin), Vec2i::new(xmax, ymax)))
+    }
+}
+
+/// Sign of the turn a -> b -> c: 1, -1, or 0 if collinear.
+fn orientation(a: Vec2i, b: Vec2i, c: Vec2i) -> i64 {
+    (b - a).cross(c - a).signum()
+}
+
+/// Closed axis-aligned rectangle [min.x, max.x] x [min.y, max.y].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Rect {
+    pub min: Vec2i,
+    pub max: Vec2i,
+}
+
+impl Rect {
+    /// Create rectangle spanned by two opposite corners.
+    pub fn from_corners(a: Vec2i, b: Vec2i) -> Self {
+        Rect {
+            min: Vec2i::new(min(a.x(), b.x()), min(a.y(), b.y())),
+            max: Vec2i::new(max(a.x(), b.x()), max(a.y(), b.y())),
+        }
+    }
+
+    pub fn width(&self) -> i64 {
+        self.max.x() - self.min.x()
+    }
+
+    pub fn height(&self) -> i64 {
+        self.max.y() - self.min.y()
+    }
+
+    /// Geometric area.
+    pub fn area(&self) -> i64 {
+        self.width() * self.height()
+    }
+
+    /// Number of lattice points in the rectangle, boundary included.
+    pub fn lattice_points(&self) -> i64 {
+        (self.width() + 1) * (self.height() + 1)
+    }
+
+    /// Check if p lies in the (closed) rectangle.
+    pub fn contains(&self, p: &Vec2i) -> bool {
+        (self.min.x()..=self.max.x()).contains(&p.x())
+            && (self.min.y()..=self.max.y()).contains(&p.y())
+    }
+
+    /// Intersection of two rectangles.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let lo = Vec2i::new(
+            max(self.min.x(), other.min.x()),
+            max(self.min.y(), other.min.y()),
+        );
+        let hi = Vec2i::new(
+            min(self.max.x(), other.max.x()),
+            min(self.max.y(), other.max.y()),
+        );
+        (lo.x() <= hi.x() && lo.y() <= hi.y()).then_some(Rect { min: lo, max: hi })
+    }
+
+    /// Corners in order around the rectangle.
+    pub fn corners(&self) -> [Vec2i; 4] {
+        [
+            self.min,
+            Vec2i::new(self.max.x(), self.min.y()),
+            self.max,
+            Vec2i::new(self.min.x(), self.max.y()),
+        ]
+    }
+}
+
+/// Location of a point relative to a polygon.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Simple (non self-intersecting) polygon with lattice point vertices.
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    vertices: Vec<Vec2i>,
+}
+
+impl Polygon {
+    /// Create polygon from vertices in order, in either orientation.
+    pub fn new(vertices: Vec<Vec2i>) -> Self {
+        assert!(vertices.len() >= 3);
+        Polygon { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Vec2i] {
+        &self.vertices
+    }
+
+    /// Iterate over edges, including the closing edge from last to first vertex.
+    pub fn edges(&self) -> impl Iterator<Item = Segment> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| Segment::new(self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    /// Check if all edges are horizontal or vertical.
+    pub fn is_rectilinear(&self) -> bool {
+        self.edges().all(|e| e.is_vertical() || e.is_horizontal())
+    }
+
+    /// Twice the signed area (shoelace formula), positive if the vertices turn
+    /// in the direction of increasing cross product.
+    pub fn signed_area2(&self) -> i64 {
+        self.edges().map(|e| e.p0.cross(e.p1)).sum()
+    }
+
+    /// Twice the area, exact.
+    pub fn area2(&self) -> i64 {
+        self.signed_area2().abs()
+    }
+
+    /// Number of lattice points on the boundary.
+    pub fn boundary_points(&self) -> i64 {
+        self.edges().map(|e| e.lattice_points() as i64 - 1).sum()
+    }
+
+    /// Number of lattice points strictly inside (Pick's theorem).
+    pub fn interior_points(&self) -> i64 {
+        // A = I + B/2 - 1  =>  I = (2A - B + 2) / 2
+        (self.area2() - self.boundary_points() + 2) / 2
+    }
+
+    /// Number of lattice points inside or on the boundary.
+    pub fn lattice_points(&self) -> i64 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    /// Locate point relative to the polygon.
+    pub fn locate(&self, p: &Vec2i) -> Location {
+        self.locate_scaled(p, 1)
+    }
+
+    /// Locate point q relative to the polygon scaled by s, allowing queries at half-integer
+    /// points with s = 2.
+    fn locate_scaled(&self, q: &Vec2i, s: i64) -> Location {
+        let mut inside = false;
+        for e in self.edges() {
+            let (a, b) = (e.p0 * s, e.p1 * s);
+            if Segment::new(a, b).contains(q) {
+                return Location::Boundary;
+            }
+            // ray casting towards +x
+            if (a.y() > q.y()) != (b.y() > q.y()) {
+                let c = (b - a).cross(*q - a);
+                if (c > 0) == (b.y() > a.y()) {
+                    inside = !inside;
+                }
+            }
+        }
+        if inside {
+            Location::Inside
+        } else {
+            Location::Outside
+        }
+    }
+
+    /// Check if the (closed) rectangle lies fully inside the (closed) polygon.
+    ///
+    /// Requires a rectilinear polygon.
+    pub fn contains_rect(&self, rect: &Rect) -> bool {
+        debug_assert!(self.is_rectilinear());
+        if rect.width() == 0 || rect.height() == 0 {
+            return self.contains_degenerate_rect(rect);
+        }
+
+        // the boundary may not pass through the open interior of the rectangle
+        let (x0, x1, y0, y1) = (rect.min.x(), rect.max.x(), rect.min.y(), rect.max.y());
+        let crosses_interior = self.edges().any(|e| {
+            if e.is_horizontal() {
+                y0 < e.p0.y() && e.p0.y() < y1 && max(e.xmin(), x0) < min(e.xmax(), x1)
+            } else {
+                x0 < e.p0.x() && e.p0.x() < x1 && max(e.ymin(), y0) < min(e.ymax(), y1)
+            }
+        });
+        if crosses_interior {
+            return false;
+        }
+
+        // now the open interior is either fully inside or fully outside
+        self.locate_scaled(&(rect.min + rect.max), 2) == Location::Inside
+    }
+
+    /// Rectangle with zero width or height is a segment (or point). Along it, the location
+    /// can only change at vertex coordinates, so check those and the points in between.
+    fn contains_degenerate_rect(&self, rect: &Rect) -> bool {
+        let vertical = rect.width() == 0;
+        let coord = |p: &Vec2i| if vertical { p.y() } else { p.x() };
+        let (lo, hi) = (coord(&rect.min), coord(&rect.max));
+
+        let mut stops: Vec<i64> = self
+            .vertices
+            .iter()
+            .map(coord)
+            .filter(|c| lo < *c && *c < hi)
+            .chain([lo, hi])
+            .collect();
+        stops.sort_unstable();
+        stops.dedup();
+
+        let point2 = |c2: i64| {
+            if vertical {
+                Vec2i::new(2 * rect.min.x(), c2)
+            } else {
+                Vec2i::new(c2, 2 * rect.min.y())
+            }
+        };
+        let stops_ok = stops
+            .iter()
+            .all(|c| self.locate_scaled(&point2(2 * c), 2) != Location::Outside);
+        let between_ok = stops
+            .windows(2)
+            .all(|w| self.locate_scaled(&point2(w[0] + w[1]), 2) != Location::Outside);
+        stops_ok && between_ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_intersects() {
+        let s = Segment::new(Vec2i::new(0, 0), Vec2i::new(4, 4));
+        assert!(s.intersects(&Segment::new(Vec2i::new(0, 4), Vec2i::new(4, 0))));
+        assert!(s.intersects(&Segment::new(Vec2i::new(2, 2), Vec2i::new(5, 0))));
+        assert!(s.intersects(&Segment::new(Vec2i::new(3, 3), Vec2i::new(6, 6))));
+        assert!(!s.intersects(&Segment::new(Vec2i::new(5, 5), Vec2i::new(6, 6))));
+        assert!(!s.intersects(&Segment::new(Vec2i::new(1, 0), Vec2i::new(4, 3))));
+        assert_eq!(s.lattice_points(), 5);
+    }
+
+    #[test]
+    fn test_segment_intersection() {
+        let s = Segment::new(Vec2i::new(1, 1), Vec2i::new(1, 8));
+        assert_eq!(
+            s.intersection(&Segment::new(Vec2i::new(5, 3), Vec2i::new(-3, 3))),
+            Some(Segment::new(Vec2i::new(1, 3), Vec2i::new(1, 3)))
+        );
+        assert_eq!(
+            s.intersection(&Segment::new(Vec2i::new(1, 5), Vec2i::new(1, 10))),
+            Some(Segment::new(Vec2i::new(1, 5), Vec2i::new(1, 8)))
+        );
+        assert_eq!(
+            s.intersection(&Segment::new(Vec2i::new(2, 3), Vec2i::new(5, 3))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rect() {
+        let r = Rect::from_corners(Vec2i::new(4, 1), Vec2i::new(0, 3));
+        assert_eq!(r.min, Vec2i::new(0, 1));
+        assert_eq!(r.area(), 8);
+        assert_eq!(r.lattice_points(), 15);
+        assert!(r.contains(&Vec2i::new(4, 3)));
+        assert!(!r.contains(&Vec2i::new(5, 3)));
+
+        let other = Rect::from_corners(Vec2i::new(3, 0), Vec2i::new(10, 2));
+        assert_eq!(
+            r.intersection(&other),
+            Some(Rect::from_corners(Vec2i::new(3, 1), Vec2i::new(4, 2)))
+        );
+        assert_eq!(
+            r.intersection(&Rect::from_corners(Vec2i::new(5, 0), Vec2i::new(6, 0))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_polygon_area() {
+        // triangle with vertices (0, 0), (4, 0), (0, 3)
+        let poly = Polygon::new(vec![Vec2i::new(0, 0), Vec2i::new(4, 0), Vec2i::new(0, 3)]);
+        assert_eq!(poly.area2(), 12);
+        assert_eq!(poly.boundary_points(), 8);
+        assert_eq!(poly.interior_points(), 3);
+        assert_eq!(poly.lattice_points(), 11);
+
+        // orientation does not matter
+        let rev = Polygon::new(poly.vertices().iter().rev().copied().collect());
+        assert_eq!(rev.signed_area2(), -poly.signed_area2());
+        assert_eq!(rev.interior_points(), 3);
+    }
+
+    #[test]
+    fn test_polygon_locate() {
+        // U shape
+        let poly = Polygon::new(vec![
+            Vec2i::new(0, 0),
+            Vec2i::new(2, 0),
+            Vec2i::new(2, 4),
+            Vec2i::new(4, 4),
+            Vec2i::new(4, 0),
+            Vec2i::new(6, 0),
+            Vec2i::new(6, 6),
+            Vec2i::new(0, 6),
+        ]);
+        assert!(poly.is_rectilinear());
+        assert_eq!(poly.locate(&Vec2i::new(1, 1)), Location::Inside);
+        assert_eq!(poly.locate(&Vec2i::new(3, 1)), Location::Outside);
+        assert_eq!(poly.locate(&Vec2i::new(3, 4)), Location::Boundary);
+        assert_eq!(poly.locate(&Vec2i::new(3, 5)), Location::Inside);
+        assert_eq!(poly.locate(&Vec2i::new(2, 2)), Location::Boundary);
+        assert_eq!(poly.locate(&Vec2i::new(-1, 0)), Location::Outside);
+        assert_eq!(poly.locate(&Vec2i::new(7, 6)), Location::Outside);
+        assert_eq!(poly.lattice_points(), 7 * 7 - 4);
+    }
+
+    #[test]
+    fn test_polygon_contains_rect() {
+        let poly = Polygon::new(vec![
+            Vec2i::new(0, 0),
+            Vec2i::new(2, 0),
+            Vec2i::new(2, 4),
+            Vec2i::new(4, 4),
+            Vec2i::new(4, 0),
+            Vec2i::new(6, 0),
+            Vec2i::new(6, 6),
+            Vec2i::new(0, 6),
+        ]);
+        let rect = |x0, y0, x1, y1| Rect::from_corners(Vec2i::new(x0, y0), Vec2i::new(x1, y1));
+        assert!(poly.contains_rect(&rect(0, 0, 2, 6)));
+        assert!(poly.contains_rect(&rect(0, 4, 6, 6)));
+        assert!(!poly.contains_rect(&rect(0, 3, 6, 6)));
+        assert!(!poly.contains_rect(&rect(2, 0, 4, 4))); // the notch itself
+        assert!(!poly.contains_rect(&rect(1, 1, 5, 2)));
+
+        // degenerate rectangles
+        assert!(poly.contains_rect(&rect(0, 4, 6, 4)));
+        assert!(!poly.contains_rect(&rect(0, 2, 6, 2)));
+        assert!(poly.contains_rect(&rect(2, 0, 2, 6)));
+        assert!(poly.contains_rect(&rect(3, 4, 3, 4)));
+        assert!(!poly.contains_rect(&rect(3, 3, 3, 3)));
+    }
+
+    #[test]
+    fn test_polygon_largest_rect() {
+        // example from 2025 day 9
+        let corners: Vec<_> = [
+            (7, 1),
+            (11, 1),
+            (11, 7),
+            (9, 7),
+            (9, 5),
+            (2, 5),
+            (2, 3),
+            (7, 3),
+        ]
+        .into_iter()
+        .map(|(x, y)| Vec2i::new(x, y))
+        .collect();
+        let poly = Polygon::new(corners.clone());
+        let largest = corners
+            .iter()
+            .flat_map(|a| corners.iter().map(|b| Rect::from_corners(*a, *b)))
+            .filter(|r| poly.contains_rect(r))
+            .map(|r| r.lattice_points())
+            .max();
+        assert_eq!(largest, Some(24));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::map2d::Map;
+use crate::vector::Vector;
+use std::ops::{Index, IndexMut};
+
+/// Dense N-dimensional grid indexed by Vector<N, i64>.
+///
+/// Component 0 varies fastest in memory, so for N = 2 the layout is the same as Map<T>
+/// with component 0 as x and component 1 as y.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridN<const N: usize, T> {
+    shape: [usize; N],
+    data: Vec<T>,
+}
+
+impl<const N: usize, T> GridN<N, T> {
+    /// Create grid filled with default values.
+    pub fn new(shape: [usize; N]) -> Self
+    where
+        T: Clone + Default,
+    {
+        Self::new_constant(shape, T::default())
+    }
+
+    /// Create grid filled with constant.
+    pub fn new_constant(shape: [usize; N], t: T) -> Self
+    where
+        T: Clone,
+    {
+        GridN {
+            shape,
+            data: vec![t; shape.iter().product()],
+        }
+    }
+
+    /// Embed a 2D map as the layer where all components beyond x and y are zero.
+    pub fn from_map(map: &Map<T>, shape: [usize; N]) -> Self
+    where
+        T: Clone + Default,
+    {
+        assert!(N >= 2 && shape[0] >= map.w && shape[1] >= map.h);
+        let mut ret = Self::new(shape);
+        for (p, t) in map.iter() {
+            let mut q = Vector::zero();
+            (q[0], q[1]) = (p.x(), p.y());
+            ret[&q] = t.clone();
+        }
+        ret
+    }
+
+    /// Extent along each axis.
+    pub fn shape(&self) -> [usize; N] {
+        self.shape
+    }
+
+    /// Total number of cells.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Check if coordinate is within grid bounds.
+    pub fn contains(&self, p: &Vector<N, i64>) -> bool {
+        (0..N).all(|i| p[i] >= 0 && (p[i] as usize) < self.shape[i])
+    }
+
+    fn linear_idx(&self, p: &Vector<N, i64>) -> usize {
+        (0..N)
+            .rev()
+            .fold(0, |acc, i| acc * self.shape[i] + p[i] as usize)
+    }
+
+    /// Coordinate of the cell at a linear index.
+    fn coord(&self, mut idx: usize) -> Vector<N, i64> {
+        let mut ret = Vector::zero();
+        for i in 0..N {
+            ret[i] = (idx % self.shape[i]) as i64;
+            idx /= self.shape[i];
+        }
+        ret
+    }
+
+    pub fn get(&self, p: &Vector<N, i64>) -> Option<&T> {
+        self.contains(p).then(|| &self.data[self.linear_idx(p)])
+    }
+
+    pub fn get_mut(&mut self, p: &Vector<N, i64>) -> Option<&mut T> {
+        match self.contains(p) {
+            true => {
+                let idx = self.linear_idx(p);
+                Some(&mut self.data[idx])
+            }
+            false => None,
+        }
+    }
+
+    /// Iterate over coordinates in memory order.
+    pub fn iter_coords(&self) -> impl Iterator<Item = Vector<N, i64>> + '_ {
+        (0..self.len()).map(|idx| self.coord(idx))
+    }
+
+    pub fn iter_values(&self) -> impl Iterator<Item = &T> {
+        self.data.iter()
+    }
+
+    pub fn iter_values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.data.iter_mut()
+    }
+
+    /// Iterate over (coord, val) pairs in memory order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vector<N, i64>, &T)> {
+        self.iter_coords().zip(self.iter_values())
+    }
+
+    /// Iterate over orthogonal neighbours of a position that are within the grid.
+    pub fn neighbours(&self, p: &Vector<N, i64>) -> impl Iterator<Item = (Vector<N, i64>, &T)> {
+        p.orthogonal_neighbours()
+            .filter_map(|q| self.get(&q).map(|t| (q, t)))
+    }
+
+    /// Iterate over all 3^N - 1 surrounding positions that are within the grid.
+    pub fn neighbours_full(
+        &self,
+        p: &Vector<N, i64>,
+    ) -> impl Iterator<Item = (Vector<N, i64>, &T)> {
+        p.chebyshev_neighbours()
+            .filter_map(|q| self.get(&q).map(|t| (q, t)))
+    }
+
+    /// Extract the 2D slice through at spanned by x_axis and y_axis as a Map.
+    ///
+    /// The components of at along x_axis and y_axis are ignored.
+    pub fn slice(&self, x_axis: usize, y_axis: usize, at: &Vector<N, i64>) -> Map<T>
+    where
+        T: Clone,
+    {
+        assert!(x_axis != y_axis);
+        let (w, h) = (self.shape[x_axis], self.shape[y_axis]);
+        Map::from_iterators((0..h).map(|y| {
+            (0..w).map(move |x| {
+                let mut p = *at;
+                (p[x_axis], p[y_axis]) = (x as i64, y as i64);
+                self[&p].clone()
+            })
+        }))
+    }
+
+    /// All cells reachable from start through orthogonal steps between cells where
+    /// passable holds, in breadth first order. Empty if start itself is not passable.
+    pub fn flood_fill<F>(&self, start: &Vector<N, i64>, passable: F) -> Vec<Vector<N, i64>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        if !self.get(start).is_some_and(&passable) {
+            return Vec::new();
+        }
+        let mut visited = vec![false; self.len()];
+        visited[self.linear_idx(start)] = true;
+        let mut ret = vec![*start];
+        let mut i = 0;
+        while i < ret.len() {
+            let p = ret[i];
+            i += 1;
+            for (q, t) in self.neighbours(&p) {
+                let idx = self.linear_idx(&q);
+                if !visited[idx] && passable(t) {
+                    visited[idx] = true;
+                    ret.push(q);
+                }
+            }
+        }
+        ret
+    }
+}
+
+impl<const N: usize, T> Index<&Vector<N, i64>> for GridN<N, T> {
+    type Output = T;
+    fn index(&self, p: &Vector<N, i64>) -> &Self::Output {
+        assert!(self.contains(p), "{p:?} out of bounds");
+        &self.data[self.linear_idx(p)]
+    }
+}
+
+impl<const N: usize, T> IndexMut<&Vector<N, i64>> for GridN<N, T> {
+    fn index_mut(&mut self, p: &Vector<N, i64>) -> &mut Self::Output {
+        assert!(self.contains(p), "{p:?} out of bounds");
+        let idx = self.linear_idx(p);
+        &mut self.data[idx]
+    }
+}
+
+impl<T: Clone> From<&Map<T>> for GridN<2, T> {
+    fn from(map: &Map<T>) -> Self {
+        GridN {
+            shape: [map.w, map.h],
+            data: map.iter_values().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::{Vec2i, Vec3i};
+
+    #[test]
+    fn test_grid_index() {
+        let mut grid: GridN<3, u32> = GridN::new([4, 3, 2]);
+        assert_eq!(grid.shape(), [4, 3, 2]);
+        assert_eq!(grid.len(), 24);
+        let p = Vec3i::new(3, 2, 1);
+        assert!(grid.contains(&p));
+        assert!(!grid.contains(&Vec3i::new(4, 0, 0)));
+        assert!(!grid.contains(&Vec3i::new(0, -1, 0)));
+        assert_eq!(grid.get(&Vec3i::new(0, 0, 2)), None);
+
+        grid[&p] = 7;
+        *grid.get_mut(&Vec3i::new(1, 0, 0)).unwrap() = 1;
+        assert_eq!(grid[&p], 7);
+        assert_eq!(grid.iter_values().sum::<u32>(), 8);
+        // component 0 varies fastest
+        let coords: Vec<_> = grid.iter_coords().take(5).collect();
+        assert_eq!(coords[1], Vec3i::new(1, 0, 0));
+        assert_eq!(coords[4], Vec3i::new(0, 1, 0));
+        assert_eq!(grid.iter().last(), Some((p, &7)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_grid_index_out_of_bounds() {
+        let grid: GridN<3, u32> = GridN::new([2, 2, 2]);
+        let _ = grid[&Vec3i::new(0, 2, 0)];
+    }
+
+    #[test]
+    fn test_grid_neighbours() {
+        let grid: GridN<3, u32> = GridN::new([3, 3, 3]);
+        let corner = Vec3i::zero();
+        let center = Vec3i::ones();
+        assert_eq!(grid.neighbours(&corner).count(), 3);
+        assert_eq!(grid.neighbours(&center).count(), 6);
+        assert_eq!(grid.neighbours_full(&corner).count(), 7);
+        assert_eq!(grid.neighbours_full(&center).count(), 26);
+
+        let grid: GridN<4, u32> = GridN::new([3, 3, 3, 3]);
+        assert_eq!(grid.neighbours_full(&Vector::ones()).count(), 80);
+    }
+
+    #[test]
+    fn test_grid_map_conversion() {
+        let map = Map::from_vecs(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        let grid = GridN::from(&map);
+        assert_eq!(grid.shape(), [3, 2]);
+        assert_eq!(grid.get(&Vec2i::new(2, 1)), Some(&6));
+
+        let grid: GridN<3, i32> = GridN::from_map(&map, [3, 2, 2]);
+        assert_eq!(grid[&Vec3i::new(1, 1, 0)], 5);
+        assert_eq!(grid[&Vec3i::new(1, 1, 1)], 0);
+
+        let xy = grid.slice(0, 1, &Vec3i::zero());
+        assert_eq!((xy.h, xy.w), (2, 3));
+        assert_eq!(
+            xy.iter_values().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+
+        // slice spanned by x and z through y = 1
+        let xz = grid.slice(0, 2, &Vec3i::new(0, 1, 0));
+        assert_eq!((xz.h, xz.w), (2, 3));
+        assert_eq!(xz[0], [4, 5, 6]);
+        assert_eq!(xz[1], [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_grid_flood_fill() {
+        // hollow 5x5x5 cube with walls at 1 and 3, similar to 2022 day 18
+        let mut grid: GridN<3, bool> = GridN::new([5, 5, 5]);
+        let coords: Vec<_> = grid.iter_coords().collect();
+        for p in coords {
+            grid[&p] = (1..=3).all(|i| (1..=3).contains(&p[i - 1]))
+                && (0..3).any(|i| p[i] == 1 || p[i] == 3);
+        }
+        let outside = grid.flood_fill(&Vec3i::zero(), |wall| !wall);
+        assert_eq!(outside.len(), 125 - 27);
+        assert_eq!(outside[0], Vec3i::zero());
+        let inside = grid.flood_fill(&Vec3i::new(2, 2, 2), |wall| !wall);
+        assert_eq!(inside, vec![Vec3i::new(2, 2, 2)]);
+        assert!(grid.flood_fill(&Vec3i::ones(), |wall| !wall).is_empty());
+    }
+}
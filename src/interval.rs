@@ -0,0 +1,319 @@
+use num_traits::PrimInt;
+use std::cmp::{max, min};
+
+/// Half-open integer interval [lo, hi).
+///
+/// Closed intervals [lo, hi] are created with Interval::closed and stored as [lo, hi + 1).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct Interval<T: PrimInt> {
+    pub lo: T,
+    pub hi: T,
+}
+
+impl<T: PrimInt> Interval<T> {
+    /// Create half-open interval [lo, hi).
+    pub fn new(lo: T, hi: T) -> Self {
+        Interval { lo, hi }
+    }
+
+    /// Create closed interval [lo, hi].
+    pub fn closed(lo: T, hi: T) -> Self {
+        Interval {
+            lo,
+            hi: hi + T::one(),
+        }
+    }
+
+    /// Check if interval contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.hi <= self.lo
+    }
+
+    /// Number of elements in interval.
+    pub fn len(&self) -> T {
+        if self.is_empty() {
+            T::zero()
+        } else {
+            self.hi - self.lo
+        }
+    }
+
+    /// Last element of a non-empty interval, i.e. hi of the closed interval [lo, hi].
+    pub fn last(&self) -> T {
+        self.hi - T::one()
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        self.lo <= x && x < self.hi
+    }
+
+    /// Check if intervals share at least one element.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        max(self.lo, other.lo) < min(self.hi, other.hi)
+    }
+
+    /// Check if intervals overlap or are adjacent, i.e. if their union is an interval.
+    pub fn touches(&self, other: &Self) -> bool {
+        max(self.lo, other.lo) <= min(self.hi, other.hi)
+    }
+
+    /// Intersection of two intervals, None if they don't overlap.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let ret = Interval::new(max(self.lo, other.lo), min(self.hi, other.hi));
+        (!ret.is_empty()).then_some(ret)
+    }
+
+    /// Smallest interval containing both intervals.
+    pub fn hull(&self, other: &Self) -> Self {
+        Interval::new(min(self.lo, other.lo), max(self.hi, other.hi))
+    }
+}
+
+/// Set of integers stored as sorted, disjoint and non-adjacent intervals.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct IntervalSet<T: PrimInt> {
+    ivals: Vec<Interval<T>>,
+}
+
+impl<T: PrimInt> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        IntervalSet { ivals: Vec::new() }
+    }
+
+    /// Number of disjoint intervals.
+    pub fn num_intervals(&self) -> usize {
+        self.ivals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ivals.is_empty()
+    }
+
+    /// Total number of elements covered.
+    pub fn len(&self) -> T {
+        self.ivals
+            .iter()
+            .fold(T::zero(), |acc, ival| acc + ival.len())
+    }
+
+    /// Iterate over disjoint intervals in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.ivals.iter()
+    }
+
+    /// Range of stored intervals that overlap or are adjacent to ival.
+    fn touching(&self, ival: &Interval<T>) -> std::ops::Range<usize> {
+        let start = self.ivals.partition_point(|x| x.hi < ival.lo);
+        let end = self.ivals.partition_point(|x| x.lo <= ival.hi);
+        start..end
+    }
+
+    /// Add all elements of ival, merging with overlapping and adjacent intervals.
+    pub fn insert(&mut self, ival: Interval<T>) {
+        if ival.is_empty() {
+            return;
+        }
+        let range = self.touching(&ival);
+        let merged = self.ivals[range.clone()]
+            .iter()
+            .fold(ival, |acc, x| acc.hull(x));
+        self.ivals.splice(range, [merged]);
+    }
+
+    /// Remove all elements of ival.
+    pub fn remove(&mut self, ival: Interval<T>) {
+        if ival.is_empty() {
+            return;
+        }
+        let start = self.ivals.partition_point(|x| x.hi <= ival.lo);
+        let end = self.ivals.partition_point(|x| x.lo < ival.hi);
+        if start >= end {
+            return;
+        }
+        let first = self.ivals[start];
+        let last = self.ivals[end - 1];
+        let remainder = [
+            Interval::new(first.lo, ival.lo),
+            Interval::new(ival.hi, last.hi),
+        ];
+        self.ivals
+            .splice(start..end, remainder.into_iter().filter(|x| !x.is_empty()));
+    }
+
+    /// Check if x is an element of the set, in O(log n).
+    pub fn contains(&self, x: T) -> bool {
+        let idx = self.ivals.partition_point(|ival| ival.hi <= x);
+        self.ivals.get(idx).is_some_and(|ival| ival.contains(x))
+    }
+
+    /// Interval containing x, if any, in O(log n).
+    pub fn interval_containing(&self, x: T) -> Option<&Interval<T>> {
+        let idx = self.ivals.partition_point(|ival| ival.hi <= x);
+        self.ivals.get(idx).filter(|ival| ival.contains(x))
+    }
+
+    /// Set of elements in either set.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ret = self.clone();
+        for ival in other.iter() {
+            ret.insert(*ival);
+        }
+        ret
+    }
+
+    /// Set of elements in both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ivals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ivals.len() && j < other.ivals.len() {
+            let (a, b) = (&self.ivals[i], &other.ivals[j]);
+            if let Some(x) = a.intersection(b) {
+                ivals.push(x);
+            }
+            if a.hi < b.hi {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ivals }
+    }
+
+    /// Set of elements in self but not in other.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ret = self.clone();
+        for ival in other.iter() {
+            ret.remove(*ival);
+        }
+        ret
+    }
+}
+
+impl<T: PrimInt> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut ret = IntervalSet::new();
+        for ival in iter {
+            ret.insert(ival);
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval() {
+        let a = Interval::closed(3, 5);
+        assert_eq!(a, Interval::new(3, 6));
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.last(), 5);
+        assert!(a.contains(5));
+        assert!(!a.contains(6));
+
+        let b = Interval::new(6, 10);
+        assert!(!a.overlaps(&b));
+        assert!(a.touches(&b));
+        assert_eq!(a.intersection(&b), None);
+        assert_eq!(a.hull(&b), Interval::new(3, 10));
+        assert_eq!(
+            b.intersection(&Interval::new(8, 20)),
+            Some(Interval::new(8, 10))
+        );
+
+        assert!(Interval::new(5, 5).is_empty());
+        assert_eq!(Interval::new(5, 2).len(), 0);
+    }
+
+    #[test]
+    fn test_interval_set_insert() {
+        let mut set = IntervalSet::new();
+        set.insert(Interval::closed(10, 14));
+        set.insert(Interval::closed(3, 5));
+        set.insert(Interval::closed(16, 20));
+        assert_eq!(set.num_intervals(), 3);
+
+        set.insert(Interval::closed(12, 18));
+        assert_eq!(
+            set.iter().copied().collect::<Vec<_>>(),
+            vec![Interval::closed(3, 5), Interval::closed(10, 20)]
+        );
+        assert_eq!(set.len(), 14);
+
+        // adjacent intervals are merged
+        set.insert(Interval::closed(6, 9));
+        assert_eq!(set.num_intervals(), 1);
+        assert_eq!(set.len(), 18);
+    }
+
+    #[test]
+    fn test_interval_set_remove() {
+        let mut set: IntervalSet<i64> = [Interval::new(0, 10), Interval::new(20, 30)]
+            .into_iter()
+            .collect();
+        set.remove(Interval::new(5, 25));
+        assert_eq!(
+            set.iter().copied().collect::<Vec<_>>(),
+            vec![Interval::new(0, 5), Interval::new(25, 30)]
+        );
+        set.remove(Interval::new(26, 28));
+        assert_eq!(set.num_intervals(), 3);
+        set.remove(Interval::new(-10, 100));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_interval_set_contains() {
+        let set: IntervalSet<u32> = [Interval::closed(3, 5), Interval::closed(10, 20)]
+            .into_iter()
+            .collect();
+        let members: Vec<_> = [1, 5, 8, 11, 17, 32]
+            .into_iter()
+            .filter(|x| set.contains(*x))
+            .collect();
+        assert_eq!(members, vec![5, 11, 17]);
+        assert_eq!(set.interval_containing(12), Some(&Interval::closed(10, 20)));
+        assert_eq!(set.interval_containing(6), None);
+    }
+
+    #[test]
+    fn test_interval_set_operations() {
+        let a: IntervalSet<i32> = [Interval::new(0, 10), Interval::new(20, 30)]
+            .into_iter()
+            .collect();
+        let b: IntervalSet<i32> = [Interval::new(5, 25), Interval::new(28, 40)]
+            .into_iter()
+            .collect();
+
+        let union = a.union(&b);
+        assert_eq!(
+            union.iter().copied().collect::<Vec<_>>(),
+            vec![Interval::new(0, 40)]
+        );
+
+        let intersection = a.intersection(&b);
+        assert_eq!(
+            intersection.iter().copied().collect::<Vec<_>>(),
+            vec![
+                Interval::new(5, 10),
+                Interval::new(20, 25),
+                Interval::new(28, 30)
+            ]
+        );
+
+        let difference = a.difference(&b);
+        assert_eq!(
+            difference.iter().copied().collect::<Vec<_>>(),
+            vec![Interval::new(0, 5), Interval::new(25, 28)]
+        );
+        assert_eq!(difference.len() + intersection.len(), a.len());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::dir::Dir;
+use crate::hash::{FxHashMap, FxHashMapBuilder};
+use crate::map2d::{Map, MapParseError};
+use crate::math::nchoosek_iter;
+use crate::vector::Vec2i;
+
+/// Layout of the numeric door keypad, '#' marks the gap.
+pub const NUMERIC_LAYOUT: &str = "789\n456\n123\n#0A";
+
+/// Layout of the directional robot keypad, '#' marks the gap.
+pub const DIRECTIONAL_LAYOUT: &str = "#^A\n<v>";
+
+/// Grid of buttons with gaps that the pointer may not pass over.
+#[derive(Debug, Clone)]
+pub struct Keypad {
+    pub map: Map<Option<char>>,
+    positions: FxHashMap<char, Vec2i>,
+}
+
+impl Keypad {
+    /// Parse keypad from a text layout where '#' or ' ' marks a gap.
+    pub fn parse(layout: &str) -> Result<Self, MapParseError> {
+        let (map, []) = Map::parse(layout, [], |c| match c {
+            '#' | ' ' => Ok(None),
+            c => Ok(Some(c)),
+        })?;
+        let positions = map
+            .iter()
+            .filter_map(|(pos, c)| Some(((*c)?, pos)))
+            .collect();
+        Ok(Keypad { map, positions })
+    }
+
+    pub fn numeric() -> Self {
+        Keypad::parse(NUMERIC_LAYOUT).unwrap()
+    }
+
+    pub fn directional() -> Self {
+        Keypad::parse(DIRECTIONAL_LAYOUT).unwrap()
+    }
+
+    /// Position of a button.
+    pub fn position(&self, button: char) -> Option<Vec2i> {
+        self.positions.get(&button).copied()
+    }
+
+    /// Check if the pointer can be on pos.
+    pub fn is_button(&self, pos: &Vec2i) -> bool {
+        self.map.contains(pos) && self.map[pos].is_some()
+    }
+
+    /// All shortest arrow sequences moving the pointer from one button to another
+    /// without passing over a gap. The sequences do not include the final 'A' press.
+    pub fn paths(&self, from: char, to: char) -> Vec<Vec<Dir>> {
+        let (Some(p0), Some(p1)) = (self.position(from), self.position(to)) else {
+            return Vec::new();
+        };
+        let d = p1 - p0;
+        let dx = if d.x() < 0 { Dir::W } else { Dir::E };
+        let dy = if d.y() < 0 { Dir::N } else { Dir::S };
+        let n = d.manhattan_norm() as usize;
+
+        nchoosek_iter(n, d.x().unsigned_abs() as usize)
+            .map(|selection| {
+                let mut path = vec![dy; n];
+                for i in selection {
+                    path[i] = dx;
+                }
+                path
+            })
+            .filter(|path| {
+                path.iter()
+                    .scan(p0, |pos, dir| {
+                        *pos = pos.step(*dir, 1);
+                        Some(*pos)
+                    })
+                    .all(|pos| self.is_button(&pos))
+            })
+            .collect()
+    }
+}
+
+/// Planner for a chain of keypads where each keypad is operated by a robot that is
+/// controlled from the next keypad in the chain. The last keypad is pressed directly.
+///
+/// All keypads except the first must be directional, i.e. contain the buttons ^>v<A.
+pub struct KeypadChain {
+    keypads: Vec<Keypad>,
+    memo: FxHashMap<(char, char, usize), u64>,
+}
+
+impl KeypadChain {
+    pub fn new(keypads: Vec<Keypad>) -> Self {
+        assert!(!keypads.is_empty());
+        KeypadChain {
+            keypads,
+            memo: FxHashMap::new(),
+        }
+    }
+
+    /// Door keypad followed by a number of directional keypads.
+    pub fn with_directional(first: Keypad, num_directional: usize) -> Self {
+        let mut keypads = vec![first];
+        keypads.extend(std::iter::repeat_n(Keypad::directional(), num_directional));
+        KeypadChain::new(keypads)
+    }
+
+    /// Minimal number of presses on the last keypad to move the pointer on keypad depth
+    /// from one button to another and press it.
+    ///
+    /// When a button on keypad depth is pressed, all pointers further down the chain are
+    /// at 'A', so the cost only depends on (from, to, depth) and can be memoized.
+    pub fn cost(&mut self, from: char, to: char, depth: usize) -> u64 {
+        if depth + 1 == self.keypads.len() {
+            return 1;
+        }
+        if let Some(cost) = self.memo.get(&(from, to, depth)) {
+            return *cost;
+        }
+
+        let paths = self.keypads[depth].paths(from, to);
+        let cost = paths
+            .iter()
+            .map(|path| {
+                let presses = path.iter().map(|d| d.to_char().unwrap());
+                self.sequence_cost_at(presses.chain(['A']), depth + 1)
+            })
+            .min()
+            .unwrap_or_else(|| panic!("No path from '{from}' to '{to}'"));
+
+        self.memo.insert((from, to, depth), cost);
+        cost
+    }
+
+    /// Minimal number of presses on the last keypad to type a sequence on keypad depth,
+    /// starting from 'A'.
+    fn sequence_cost_at<I: IntoIterator<Item = char>>(&mut self, seq: I, depth: usize) -> u64 {
+        let mut prev = 'A';
+        let mut ret = 0;
+        for c in seq {
+            ret += self.cost(prev, c, depth);
+            prev = c;
+        }
+        ret
+    }
+
+    /// Minimal number of presses on the last keypad to type code on the first keypad.
+    pub fn sequence_cost(&mut self, code: &str) -> u64 {
+        self.sequence_cost_at(code.chars(), 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keypad_parse() {
+        let keypad = Keypad::numeric();
+        assert_eq!(keypad.position('7'), Some(Vec2i::new(0, 0)));
+        assert_eq!(keypad.position('A'), Some(Vec2i::new(2, 3)));
+        assert_eq!(keypad.position('#'), None);
+        assert!(!keypad.is_button(&Vec2i::new(0, 3)));
+        assert!(Keypad::parse("12\n3").is_err());
+    }
+
+    #[test]
+    fn test_keypad_paths() {
+        let keypad = Keypad::numeric();
+        // paths must avoid the gap in the bottom left corner
+        assert_eq!(
+            keypad.paths('A', '1'),
+            vec![vec![Dir::W, Dir::N, Dir::W], vec![Dir::N, Dir::W, Dir::W]]
+        );
+        assert_eq!(keypad.paths('5', '9').len(), 2);
+        assert_eq!(keypad.paths('3', '3'), vec![vec![]]);
+    }
+
+    #[test]
+    fn test_keypad_chain() {
+        // typing directly on the numeric keypad
+        let mut chain = KeypadChain::with_directional(Keypad::numeric(), 0);
+        assert_eq!(chain.sequence_cost("029A"), 4);
+
+        // examples from 2024 day 21
+        let mut chain = KeypadChain::with_directional(Keypad::numeric(), 1);
+        assert_eq!(chain.sequence_cost("029A"), "<A^A>^^AvvvA".len() as u64);
+        let mut chain = KeypadChain::with_directional(Keypad::numeric(), 3);
+        assert_eq!(chain.sequence_cost("029A"), 68);
+        assert_eq!(chain.sequence_cost("379A"), 64);
+    }
+
+    #[test]
+    fn test_keypad_chain_custom_layout() {
+        // a single row keypad operated from one directional keypad
+        let keypad = Keypad::parse("A12").unwrap();
+        let mut chain = KeypadChain::with_directional(keypad, 1);
+        // >A >A
+        assert_eq!(chain.sequence_cost("12"), 4);
+        // >>A A <<A
+        assert_eq!(chain.sequence_cost("22A"), 7);
+    }
+}
@@ -1,18 +1,33 @@
 use std::fmt;
 use std::path::PathBuf;
 
+pub mod aabb;
+pub mod bitmap2d;
+pub mod byte_trie;
+pub mod circuit;
+pub mod combinatorics;
+pub mod compress;
 pub mod container;
 pub mod dir;
 pub mod dsa;
+pub mod geometry;
+pub mod grid;
 pub mod hash;
 pub mod heap;
+pub mod interval;
+pub mod keypad;
 pub mod map2d;
 pub mod math;
+pub mod matrix;
+pub mod modint;
 pub mod parsing;
+pub mod rational;
 pub mod sol2024;
 pub mod sol2025;
+pub mod sparse_map;
 pub mod trie;
 pub mod vector;
+pub mod vm;
 
 #[derive(Debug, PartialEq, Default)]
 pub enum Answer {
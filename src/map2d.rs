@@ -4,6 +4,42 @@ use std::{fmt, str::Lines};
 
 pub use std::ops::{Index, IndexMut};
 
+/// Error when parsing a map from text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapParseError {
+    /// Input contains no lines.
+    Empty,
+    /// Line has a different length than the first line.
+    RaggedLine {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// Character could not be converted to a tile.
+    InvalidChar { pos: Vec2i, msg: String },
+    /// Marker character not present in input.
+    MissingMarker(char),
+}
+
+impl fmt::Display for MapParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty map"),
+            Self::RaggedLine {
+                line,
+                expected,
+                found,
+            } => write!(f, "line {line} has length {found}, expected {expected}"),
+            Self::InvalidChar { pos, msg } => {
+                write!(f, "invalid char at ({}, {}): {msg}", pos.x(), pos.y())
+            }
+            Self::MissingMarker(c) => write!(f, "marker '{c}' not found"),
+        }
+    }
+}
+
+impl std::error::Error for MapParseError {}
+
 /// 2D map type.
 ///
 /// Map<T> is indexed by Vec2i using "image" coordinates, i.e.
@@ -69,6 +105,58 @@ impl<T> Map<T> {
         Map::from_iterators(iters)
     }
 
+    /// Parse map from puzzle text.
+    ///
+    /// Every character (markers included) is converted with f. Returns the map together
+    /// with the position of the first occurrence of each marker character.
+    ///
+    /// Leading and trailing empty lines and Windows line endings are ignored.
+    pub fn parse<F, const N: usize>(
+        input: &str,
+        markers: [char; N],
+        f: F,
+    ) -> Result<(Map<T>, [Vec2i; N]), MapParseError>
+    where
+        F: Fn(char) -> Result<T, String>,
+    {
+        let lines: Vec<&str> = input.lines().skip_while(|l| l.is_empty()).collect();
+        let h = lines
+            .iter()
+            .rposition(|l| !l.is_empty())
+            .ok_or(MapParseError::Empty)?
+            + 1;
+        let w = lines[0].chars().count();
+
+        let mut data = Vec::with_capacity(h * w);
+        let mut found: [Option<Vec2i>; N] = [None; N];
+        for (y, line) in lines[..h].iter().enumerate() {
+            let len = line.chars().count();
+            if len != w {
+                return Err(MapParseError::RaggedLine {
+                    line: y,
+                    expected: w,
+                    found: len,
+                });
+            }
+            for (x, c) in line.chars().enumerate() {
+                let pos = Vec2i::new(x as i64, y as i64);
+                for (marker, found) in markers.iter().zip(found.iter_mut()) {
+                    if *marker == c && found.is_none() {
+                        *found = Some(pos);
+                    }
+                }
+                data.push(f(c).map_err(|msg| MapParseError::InvalidChar { pos, msg })?);
+            }
+        }
+
+        let mut positions = [Vec2i::zero(); N];
+        for ((marker, found), pos) in markers.iter().zip(found).zip(positions.iter_mut()) {
+            *pos = found.ok_or(MapParseError::MissingMarker(*marker))?;
+        }
+
+        Ok((Map { h, w, data }, positions))
+    }
+
     /// Create map from 2d matrix.
     pub fn from_vecs(vecs: Vec<Vec<T>>) -> Map<T> {
         let h = vecs.len();
@@ -124,6 +212,11 @@ impl<T> Map<T> {
         self.data.iter_mut()
     }
 
+    /// Consume map and iterate over values in row-major order.
+    pub fn into_values(self) -> impl Iterator<Item = T> {
+        self.data.into_iter()
+    }
+
     /// Iterate over (coord, val) pairs.
     pub fn iter(&self) -> impl Iterator<Item = (Vec2i, &T)> {
         self.iter_coords().zip(self.iter_values())
@@ -134,6 +227,77 @@ impl<T> Map<T> {
         self.iter_coords().zip(self.iter_values_mut())
     }
 
+    /// Iterate over (coord, val) pairs in row y, west to east.
+    pub fn iter_row(&self, y: usize) -> impl Iterator<Item = (Vec2i, &T)> {
+        self[y]
+            .iter()
+            .enumerate()
+            .map(move |(x, t)| (Vec2i::new(x as i64, y as i64), t))
+    }
+
+    /// Iterate over (coord, val) pairs in column x, north to south.
+    pub fn iter_col(&self, x: usize) -> impl Iterator<Item = (Vec2i, &T)> {
+        assert!(x < self.w, "column {x} out of range");
+        self.data[x..]
+            .iter()
+            .step_by(self.w)
+            .enumerate()
+            .map(move |(y, t)| (Vec2i::new(x as i64, y as i64), t))
+    }
+
+    /// Iterate over (coord, val) pairs from pos (inclusive) in direction dir until the edge.
+    pub fn iter_ray(
+        &self,
+        pos: &Vec2i,
+        dir: Dir,
+    ) -> impl Iterator<Item = (Vec2i, &T)> + use<'_, T> {
+        let start = self.get(pos).map(|t| (*pos, t));
+        std::iter::successors(start, move |(p, _)| {
+            let new = p.step(dir, 1);
+            self.get(&new).map(|t| (new, t))
+        })
+    }
+
+    /// Iterate over (coord, val) pairs from pos (inclusive) in direction dir until the edge,
+    /// or until reaching an element for which stop returns true (exclusive).
+    pub fn iter_ray_until<F>(
+        &self,
+        pos: &Vec2i,
+        dir: Dir,
+        stop: F,
+    ) -> impl Iterator<Item = (Vec2i, &T)> + use<'_, T, F>
+    where
+        F: Fn(&Vec2i, &T) -> bool,
+    {
+        self.iter_ray(pos, dir)
+            .take_while(move |(p, t)| !stop(p, t))
+    }
+
+    /// Iterate over all diagonals (north-west to south-east).
+    ///
+    /// Diagonals are ordered by their starting position, starting in the south-west
+    /// corner, moving north along the west edge and then east along the north edge.
+    pub fn iter_diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = (Vec2i, &T)>> {
+        let starts_w = (0..self.h).rev().map(|y| Vec2i::new(0, y as i64));
+        let starts_n = (1..self.w).map(|x| Vec2i::new(x as i64, 0));
+        starts_w
+            .chain(starts_n)
+            .map(move |p| self.iter_ray(&p, Dir::SE))
+    }
+
+    /// Iterate over all anti-diagonals (north-east to south-west).
+    ///
+    /// Anti-diagonals are ordered by their starting position, starting in the north-west
+    /// corner, moving east along the north edge and then south along the east edge.
+    pub fn iter_antidiagonals(&self) -> impl Iterator<Item = impl Iterator<Item = (Vec2i, &T)>> {
+        let w = self.w as i64;
+        let starts_n = (0..self.w).map(|x| Vec2i::new(x as i64, 0));
+        let starts_e = (1..self.h).map(move |y| Vec2i::new(w - 1, y as i64));
+        starts_n
+            .chain(starts_e)
+            .map(move |p| self.iter_ray(&p, Dir::SW))
+    }
+
     /// Get map element.
     pub fn get(&self, p: &Vec2i) -> Option<&T> {
         match self.contains(p) {
@@ -168,6 +332,182 @@ where
     }
 }
 
+/// ANSI terminal colour used to highlight cells in a MapRender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Colour {
+    fn ansi_code(&self) -> u8 {
+        match self {
+            Colour::Red => 31,
+            Colour::Green => 32,
+            Colour::Yellow => 33,
+            Colour::Blue => 34,
+            Colour::Magenta => 35,
+            Colour::Cyan => 36,
+        }
+    }
+}
+
+/// Glyph ramp used for heat maps, from low to high.
+const HEAT_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Configurable renderer for maps.
+///
+/// Created with Map::render or Map::render_with, and displayed with "{}".
+/// Overlays and highlights are applied in insertion order, so later ones win.
+pub struct MapRender<'a, T> {
+    map: &'a Map<T>,
+    glyph: Box<dyn Fn(&T) -> String + 'a>,
+    overlays: Map<Option<char>>,
+    highlights: Map<Option<Colour>>,
+    rulers: bool,
+}
+
+impl<'a, T> MapRender<'a, T> {
+    fn new(map: &'a Map<T>, glyph: Box<dyn Fn(&T) -> String + 'a>) -> Self {
+        MapRender {
+            map,
+            glyph,
+            overlays: map.same_size_with(None),
+            highlights: map.same_size_with(None),
+            rulers: false,
+        }
+    }
+
+    /// Draw glyph on top of the given positions. Positions outside the map are ignored.
+    pub fn overlay<I>(mut self, positions: I, glyph: char) -> Self
+    where
+        I: IntoIterator<Item = Vec2i>,
+    {
+        for p in positions {
+            if let Some(el) = self.overlays.get_mut(&p) {
+                *el = Some(glyph);
+            }
+        }
+        self
+    }
+
+    /// Draw a path of 4-connected positions with arrows pointing to the next position.
+    ///
+    /// The last position is drawn as 'o'.
+    pub fn path(mut self, path: &[Vec2i]) -> Self {
+        for (i, p) in path.iter().enumerate() {
+            let glyph = match path.get(i + 1).map(|n| *n - *p) {
+                Some(d) if d == Vec2i::new(0, -1) => '^',
+                Some(d) if d == Vec2i::new(1, 0) => '>',
+                Some(d) if d == Vec2i::new(0, 1) => 'v',
+                Some(d) if d == Vec2i::new(-1, 0) => '<',
+                _ => 'o',
+            };
+            if let Some(el) = self.overlays.get_mut(p) {
+                *el = Some(glyph);
+            }
+        }
+        self
+    }
+
+    /// Colour the given positions with ANSI escape codes.
+    pub fn highlight<I>(mut self, positions: I, colour: Colour) -> Self
+    where
+        I: IntoIterator<Item = Vec2i>,
+    {
+        for p in positions {
+            if let Some(el) = self.highlights.get_mut(&p) {
+                *el = Some(colour);
+            }
+        }
+        self
+    }
+
+    /// Add row and column numbers along the top and left edges.
+    pub fn rulers(mut self, rulers: bool) -> Self {
+        self.rulers = rulers;
+        self
+    }
+}
+
+impl<T> fmt::Display for MapRender<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let map = self.map;
+        let row_width = (map.h.max(1) - 1).to_string().len();
+        if self.rulers {
+            // one header line per digit of the column index, most significant first
+            let col_digits = (map.w.max(1) - 1).to_string().len();
+            for d in (0..col_digits as u32).rev() {
+                write!(f, "{:row_width$} ", "")?;
+                for x in 0..map.w {
+                    match x >= 10usize.pow(d) || d == 0 {
+                        true => write!(f, "{}", x / 10usize.pow(d) % 10)?,
+                        false => write!(f, " ")?,
+                    }
+                }
+                writeln!(f)?;
+            }
+        }
+        for (y, row) in (0..map.h).zip(self.overlays.data.chunks(map.w.max(1))) {
+            if self.rulers {
+                write!(f, "{y:>row_width$} ")?;
+            }
+            for (x, overlay) in row.iter().enumerate() {
+                let glyph = match overlay {
+                    Some(c) => c.to_string(),
+                    None => (self.glyph)(&map[(y, x)]),
+                };
+                match self.highlights[(y, x)] {
+                    Some(colour) => write!(f, "\x1b[{}m{glyph}\x1b[0m", colour.ansi_code())?,
+                    None => write!(f, "{glyph}")?,
+                }
+            }
+            if y < map.h - 1 {
+                writeln!(f)?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T> Map<T> {
+    /// Render map using the Display implementation of T.
+    pub fn render(&self) -> MapRender<'_, T>
+    where
+        T: fmt::Display,
+    {
+        MapRender::new(self, Box::new(|t: &T| t.to_string()))
+    }
+
+    /// Render map using a custom glyph for each value.
+    pub fn render_with<'a, F>(&'a self, f: F) -> MapRender<'a, T>
+    where
+        F: Fn(&T) -> char + 'a,
+    {
+        MapRender::new(self, Box::new(move |t: &T| f(t).to_string()))
+    }
+}
+
+impl Map<bool> {
+    /// Render boolean map with '#' for true and '.' for false.
+    pub fn render_bool(&self) -> MapRender<'_, bool> {
+        self.render_with(|b| if *b { '#' } else { '.' })
+    }
+}
+
+impl Map<u32> {
+    /// Render map as a heat map, scaling values to a glyph ramp from ' ' (0) to '@' (max).
+    pub fn render_heat(&self) -> MapRender<'_, u32> {
+        let max = self.data.iter().copied().max().unwrap_or(0).max(1) as usize;
+        let n = HEAT_RAMP.len() - 1;
+        self.render_with(move |v| HEAT_RAMP[(*v as usize * n).div_ceil(max)] as char)
+    }
+}
+
 /// Index with Vec2i = (x, y).
 impl<T> Index<&Vec2i> for Map<T> {
     type Output = T;
@@ -218,6 +558,7 @@ impl<T> IndexMut<usize> for Map<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parsing::identity;
 
     #[test]
     fn test_map_construct() {
@@ -301,4 +642,202 @@ mod tests {
 
         assert_eq!(map[&key_pos], 100);
     }
+
+    #[test]
+    fn test_map_iter_row_col() {
+        let data = vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10, 11, 12]];
+        let map = Map::from_vecs(data);
+
+        let row: Vec<_> = map.iter_row(1).map(|(p, v)| (p, *v)).collect();
+        assert_eq!(
+            row,
+            vec![
+                (Vec2i::new(0, 1), 5),
+                (Vec2i::new(1, 1), 6),
+                (Vec2i::new(2, 1), 7),
+                (Vec2i::new(3, 1), 8)
+            ]
+        );
+
+        let col: Vec<_> = map.iter_col(2).map(|(p, v)| (p, *v)).collect();
+        assert_eq!(
+            col,
+            vec![
+                (Vec2i::new(2, 0), 3),
+                (Vec2i::new(2, 1), 7),
+                (Vec2i::new(2, 2), 11)
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_map_iter_col_out_of_range() {
+        let map = Map::from_vecs(vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]);
+        // would otherwise yield cells from the second row
+        let _ = map.iter_col(4);
+    }
+
+    #[test]
+    fn test_map_iter_diagonals() {
+        let data = vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10, 11, 12]];
+        let map = Map::from_vecs(data);
+
+        let diags: Vec<Vec<_>> = map
+            .iter_diagonals()
+            .map(|d| d.map(|(_, v)| *v).collect())
+            .collect();
+        assert_eq!(
+            diags,
+            vec![
+                vec![9],
+                vec![5, 10],
+                vec![1, 6, 11],
+                vec![2, 7, 12],
+                vec![3, 8],
+                vec![4]
+            ]
+        );
+
+        let antidiags: Vec<Vec<_>> = map
+            .iter_antidiagonals()
+            .map(|d| d.map(|(_, v)| *v).collect())
+            .collect();
+        assert_eq!(
+            antidiags,
+            vec![
+                vec![1],
+                vec![2, 5],
+                vec![3, 6, 9],
+                vec![4, 7, 10],
+                vec![8, 11],
+                vec![12]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_iter_ray() {
+        let data = vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10, 11, 12]];
+        let map = Map::from_vecs(data);
+
+        let ray: Vec<_> = map.iter_ray(&Vec2i::new(1, 1), Dir::E).collect();
+        assert_eq!(
+            ray,
+            vec![
+                (Vec2i::new(1, 1), &6),
+                (Vec2i::new(2, 1), &7),
+                (Vec2i::new(3, 1), &8)
+            ]
+        );
+
+        let ray: Vec<_> = map
+            .iter_ray(&Vec2i::new(3, 2), Dir::NW)
+            .map(|(_, v)| *v)
+            .collect();
+        assert_eq!(ray, vec![12, 7, 2]);
+
+        assert_eq!(map.iter_ray(&Vec2i::new(4, 0), Dir::W).count(), 0);
+
+        let ray: Vec<_> = map
+            .iter_ray_until(&Vec2i::new(0, 0), Dir::S, |_, v| *v > 5)
+            .map(|(_, v)| *v)
+            .collect();
+        assert_eq!(ray, vec![1, 5]);
+    }
+
+    #[test]
+    fn test_map_parse() {
+        let input = "\n#.S\r\n.E.\r\n#..\n\n";
+        let (map, [start, end]) = Map::parse(input, ['S', 'E'], identity).unwrap();
+        assert_eq!(map.h, 3);
+        assert_eq!(map.w, 3);
+        assert_eq!(start, Vec2i::new(2, 0));
+        assert_eq!(end, Vec2i::new(1, 1));
+        assert_eq!(map[&start], 'S');
+        assert_eq!(map[(2, 0)], '#');
+    }
+
+    #[test]
+    fn test_map_parse_errors() {
+        let to_bool = |c: char| match c {
+            '#' => Ok(true),
+            '.' | '@' => Ok(false),
+            _ => Err(format!("unknown tile {c}")),
+        };
+
+        assert_eq!(
+            Map::parse("\n\n", [], to_bool).unwrap_err(),
+            MapParseError::Empty
+        );
+        assert_eq!(
+            Map::parse("#.#\n##\n", [], to_bool).unwrap_err(),
+            MapParseError::RaggedLine {
+                line: 1,
+                expected: 3,
+                found: 2
+            }
+        );
+        assert_eq!(
+            Map::parse("#.#\n#x#\n", [], to_bool).unwrap_err(),
+            MapParseError::InvalidChar {
+                pos: Vec2i::new(1, 1),
+                msg: "unknown tile x".to_string()
+            }
+        );
+        assert_eq!(
+            Map::parse("#.#\n#.#\n", ['@'], to_bool).unwrap_err(),
+            MapParseError::MissingMarker('@')
+        );
+
+        let (map, [robot]) = Map::parse("#.#\n#@#\n", ['@'], to_bool).unwrap();
+        assert_eq!(robot, Vec2i::new(1, 1));
+        assert!(!map[&robot]);
+    }
+
+    #[test]
+    fn test_map_render() {
+        let map = Map::from_vecs(vec![vec!['.', '#', '.'], vec!['.', '.', '.']]);
+        assert_eq!(map.render().to_string(), ".#.\n...");
+
+        let path = [
+            Vec2i::new(0, 0),
+            Vec2i::new(0, 1),
+            Vec2i::new(1, 1),
+            Vec2i::new(2, 1),
+        ];
+        let rendered = map
+            .render()
+            .path(&path)
+            .overlay([Vec2i::new(2, 0), Vec2i::new(5, 5)], 'X')
+            .to_string();
+        assert_eq!(rendered, "v#X\n>>o");
+
+        let rendered = map
+            .render()
+            .highlight([Vec2i::new(1, 0)], Colour::Red)
+            .to_string();
+        assert_eq!(rendered, ".\x1b[31m#\x1b[0m.\n...");
+    }
+
+    #[test]
+    fn test_map_render_rulers() {
+        let map = Map::<bool>::new(11, 12);
+        let rendered = map.render_bool().rulers(true).to_string();
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], "             11");
+        assert_eq!(lines[1], "   012345678901");
+        assert_eq!(lines[2], " 0 ............");
+        assert_eq!(lines[12], "10 ............");
+    }
+
+    #[test]
+    fn test_map_render_heat() {
+        let map = Map::from_vecs(vec![vec![0u32, 1, 5, 9]]);
+        assert_eq!(map.render_heat().to_string(), " .+@");
+
+        let map = Map::from_vecs(vec![vec![true, false]]);
+        assert_eq!(map.render_bool().to_string(), "#.");
+    }
 }
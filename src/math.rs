@@ -1,55 +1,74 @@
+use crate::matrix::Matrix;
+use crate::rational::Rational;
+use num_traits::{Num, PrimInt, Signed, Zero};
 use std::{
     iter::Sum,
     ops::{AddAssign, Mul},
 };
 
-/// Greatest common divisor of two numbers.
-pub fn gcd(mut n: u64, mut m: u64) -> u64 {
-    while m != 0 {
-        if m < n {
-            std::mem::swap(&mut m, &mut n);
-        }
-        m %= n
+/// Greatest common divisor of two numbers, gcd(0, n) = |n|.
+///
+/// Works for any primitive integer; for signed types the result is non-negative.
+pub fn gcd<T: PrimInt>(mut n: T, mut m: T) -> T {
+    while !m.is_zero() {
+        (n, m) = (m, n % m);
+    }
+    match n < T::zero() {
+        true => T::zero() - n,
+        false => n,
     }
-    n
 }
 
-/// Least common multiple of two numbers.
-pub fn lcm(n: u64, m: u64) -> u64 {
-    n * (m / gcd(n, m))
+/// Least common multiple of two numbers, lcm(0, n) = 0. Panics on overflow.
+pub fn lcm<T: PrimInt>(n: T, m: T) -> T {
+    checked_lcm(n, m).expect("lcm overflow")
 }
 
-/// Get number of digits in an integer.
-pub fn number_length(x: u64) -> u64 {
-    let mut xc = x;
-    let mut res = 0;
-    while xc > 0 {
+/// Least common multiple of two numbers, None on overflow.
+pub fn checked_lcm<T: PrimInt>(n: T, m: T) -> Option<T> {
+    let g = gcd(n, m);
+    if g.is_zero() {
+        return Some(T::zero());
+    }
+    let l = (n / g).checked_mul(&m)?;
+    match l < T::zero() {
+        true => T::zero().checked_sub(&l),
+        false => Some(l),
+    }
+}
+
+/// Get number of decimal digits in an integer, number_length(0) = 1.
+pub fn number_length<T: PrimInt>(x: T) -> u64 {
+    let ten = T::from(10).unwrap();
+    let mut xc = x / ten;
+    let mut res = 1;
+    while !xc.is_zero() {
         res += 1;
-        xc /= 10;
+        xc = xc / ten;
     }
     res
 }
 
 /// Solve normalized Bezout's identity.
 /// Find x and y s.t. ax + by = 1,  assuming that a, b are co-prime.
-pub fn bezout(a: i64, b: i64) -> Option<(i64, i64)> {
-    if a < 0 {
+pub fn bezout<T: PrimInt + Signed>(a: T, b: T) -> Option<(T, T)> {
+    if a < T::zero() {
         return bezout(-a, b).map(|(x, y)| (-x, y));
     }
-    if b < 0 {
+    if b < T::zero() {
         return bezout(a, -b).map(|(x, y)| (x, -y));
     }
 
     let (mut ra, mut rb) = (a, b);
-    let mut qs: Vec<i64> = Vec::with_capacity(20);
+    let mut qs: Vec<T> = Vec::with_capacity(20);
 
     // collect all quotients s.t. r[i] = q[i] * r[i+1] + r[i+2]
-    while rb != 0 {
+    while !rb.is_zero() {
         qs.push(ra / rb);
         (ra, rb) = (rb, ra % rb);
     }
 
-    if ra != 1 {
+    if !ra.is_one() {
         // if gcd(a, b) > 1 we don't have a solution
         return None;
     }
@@ -63,7 +82,7 @@ pub fn bezout(a: i64, b: i64) -> Option<(i64, i64)> {
     //
     // let c1, c2 be s.t. c1 r[n] + c2 r[n+1] = 1
     // start from last line where (r[n+1], r[n+2]) = (1, 0)
-    let (mut c1, mut c2) = (1, 0);
+    let (mut c1, mut c2) = (T::one(), T::zero());
 
     // using r[k] = r[k-2] - q[k-2] * r[k-1] we can assemble everything back
     // 1 = c1 r[k] + c2 r[k+1]
@@ -80,7 +99,7 @@ pub fn bezout(a: i64, b: i64) -> Option<(i64, i64)> {
 /// Returns numbers (x0, y0, u, v) that define all solutions as
 ///   (x, y) = (x0, y0) + k (u, v) for k \in Z
 pub fn diophantine(a: i64, b: i64, c: i64) -> Option<(i64, i64, i64, i64)> {
-    let gcd_ab = gcd(a.unsigned_abs(), b.unsigned_abs()) as i64;
+    let gcd_ab = gcd(a, b);
     if c % gcd_ab != 0 {
         return None;
     }
@@ -99,22 +118,109 @@ pub fn diophantine(a: i64, b: i64, c: i64) -> Option<(i64, i64, i64, i64)> {
 /// Find x s.t.
 ///  x % n1 = a1
 ///  x % n2 = a2
+/// The moduli need not be co-prime, the solution is unique modulo lcm(n1, n2).
+/// Returns None if the equations are inconsistent or the solution does not fit in i64.
 pub fn crt2(n1: i64, a1: i64, n2: i64, a2: i64) -> Option<i64> {
-    let (m1, m2) = bezout(n1, n2)?;
-    let ret = (a1 % n1) * m2 * n2 + (a2 % n2) * m1 * n1;
-    Some(ret.rem_euclid(n1 * n2))
+    checked_crt(&[n1, n2], &[a1, a2]).map(|(x, _)| x)
 }
 
 /// Construct Chinese remainder theorem solution
 /// Find x s.t.
 ///  x % nn[i] = aa[i],  i = 1..k
+/// The solution is unique modulo the lcm of the moduli.
 pub fn crt(nn: &[i64], aa: &[i64]) -> Option<i64> {
-    let (mut n, mut x) = (nn[0], aa[0]);
+    checked_crt(nn, aa).map(|(x, _)| x)
+}
+
+/// Chinese remainder theorem solution x together with the modulus lcm(nn) it is unique for.
+/// Intermediate values are computed in i128. Returns None if the equations are
+/// inconsistent or the modulus does not fit in i64.
+pub fn checked_crt(nn: &[i64], aa: &[i64]) -> Option<(i64, i64)> {
+    let (mut n, mut x) = (
+        nn[0].abs() as i128,
+        (aa[0] as i128).rem_euclid(nn[0] as i128),
+    );
     for (ni, ai) in nn[1..].iter().zip(aa[1..].iter()) {
-        x = crt2(n, x, *ni, *ai)?;
-        n *= ni;
+        let (ni, ai) = (ni.abs() as i128, *ai as i128);
+        let g = gcd(n, ni);
+        if (ai - x) % g != 0 {
+            return None;
+        }
+        // x' = x + k n where k (n / g) = (ai - x) / g  (mod ni / g)
+        let (m, _) = bezout(n / g, ni / g)?;
+        let k = ((ai - x) / g % (ni / g)) * m % (ni / g);
+        let l = i64::try_from(n / g * ni).ok()? as i128;
+        x = (x + k * n).rem_euclid(l);
+        n = l;
     }
-    Some(x)
+    Some((x as i64, n as i64))
+}
+
+/// Compute base^exp % m by repeated squaring.
+pub fn pow_mod(base: u64, mut exp: u64, m: u64) -> u64 {
+    let m = m as u128;
+    let mut base = base as u128 % m;
+    let mut ret = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            ret = ret * base % m;
+        }
+        base = base * base % m;
+        exp >>= 1;
+    }
+    ret as u64
+}
+
+/// Sieve of Eratosthenes, is_prime[i] tells if i is prime for i in 0..=n.
+pub fn prime_sieve(n: usize) -> Vec<bool> {
+    let mut is_prime = vec![true; n + 1];
+    is_prime[0] = false;
+    if n >= 1 {
+        is_prime[1] = false;
+    }
+    let mut i = 2;
+    while i * i <= n {
+        if is_prime[i] {
+            (i * i..=n).step_by(i).for_each(|j| is_prime[j] = false);
+        }
+        i += 1;
+    }
+    is_prime
+}
+
+/// All primes up to and including n.
+pub fn primes_up_to(n: usize) -> Vec<usize> {
+    prime_sieve(n)
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| p.then_some(i))
+        .collect()
+}
+
+/// Prime factorisation of n as (prime, exponent) pairs in increasing order.
+pub fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    let mut ret = Vec::new();
+    let mut p = 2;
+    while p <= n / p {
+        if n.is_multiple_of(p) {
+            let mut e = 0;
+            while n.is_multiple_of(p) {
+                n /= p;
+                e += 1;
+            }
+            ret.push((p, e));
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    if n > 1 {
+        ret.push((n, 1));
+    }
+    ret
+}
+
+/// Euler's totient, the number of integers in 1..=n co-prime to n.
+pub fn totient(n: u64) -> u64 {
+    factorize(n).iter().fold(n, |acc, (p, _)| acc / p * (p - 1))
 }
 
 /// Enumerate all nchoosek selections.
@@ -176,65 +282,269 @@ pub fn nchoosek_iter(n: usize, k: usize) -> NChooseK {
     NChooseK::new(n, k)
 }
 
+/// Matrix product of nested vectors, see matrix::Matrix.
 pub fn gemm<T: Mul<Output = T> + Copy + Default + AddAssign>(
     a: &[Vec<T>],
     b: &[Vec<T>],
 ) -> Vec<Vec<T>> {
-    let arows = a.len();
-    let acols = a[0].len();
+    (&Matrix::from_rows(a) * &Matrix::from_rows(b)).to_rows()
+}
 
-    let brows = b.len();
-    let bcols = b[0].len();
+/// Vector-matrix product a * b, see matrix::Matrix.
+pub fn dot_vm<T: Mul<Output = T> + Copy + Default + AddAssign>(a: &[T], b: &[Vec<T>]) -> Vec<T> {
+    Matrix::from_rows(b).vec_mul(a)
+}
 
-    assert_eq!(acols, brows);
+/// Matrix-vector product a * b, see matrix::Matrix.
+pub fn dot_mv<T: Mul<Output = T> + Copy + Default + AddAssign>(a: &[Vec<T>], b: &[T]) -> Vec<T> {
+    Matrix::from_rows(a).mul_vec(b)
+}
 
-    let mut ret = vec![vec![T::default(); bcols]; arows];
-    for i in 0..arows {
-        for j in 0..bcols {
-            (0..acols).for_each(|k| {
-                ret[i][j] += a[i][k] * b[k][j];
-            });
+pub fn dot<T: Mul<Output = T> + Copy + Default + Sum>(a: &[T], b: &[T]) -> T {
+    a.iter().zip(b.iter()).map(|(a, b)| *a * *b).sum::<T>()
+}
+
+/// Divide row by the gcd of its entries.
+fn make_primitive(row: &mut [i64]) {
+    let g = row.iter().fold(0, |acc, x| gcd(acc, *x));
+    if g > 1 {
+        row.iter_mut().for_each(|x| *x /= g);
+    }
+}
+
+/// Reduced row echelon form over the integers using fraction-free elimination.
+///
+/// Rows are kept primitive (gcd of entries is 1) with positive pivots, and every pivot
+/// column is zero outside its pivot row. Returns the non-zero rows and their pivot columns.
+pub fn integer_rref(a: &[Vec<i64>]) -> (Vec<Vec<i64>>, Vec<usize>) {
+    let mut m = a.to_vec();
+    let cols = m.first().map_or(0, |row| row.len());
+    let mut pivots = Vec::new();
+
+    for col in 0..cols {
+        let r = pivots.len();
+        // smallest pivot keeps the numbers small
+        let Some(best) = (r..m.len())
+            .filter(|i| m[*i][col] != 0)
+            .min_by_key(|i| m[*i][col].abs())
+        else {
+            continue;
+        };
+        m.swap(r, best);
+        if m[r][col] < 0 {
+            m[r].iter_mut().for_each(|x| *x = -*x);
         }
+        make_primitive(&mut m[r]);
+
+        let pivot_row = m[r].clone();
+        for (i, row) in m.iter_mut().enumerate() {
+            if i == r || row[col] == 0 {
+                continue;
+            }
+            let g = gcd(pivot_row[col], row[col]);
+            let (fr, fi) = (row[col] / g, pivot_row[col] / g);
+            for (x, p) in row.iter_mut().zip(pivot_row.iter()) {
+                *x = fi * *x - fr * p;
+            }
+            make_primitive(row);
+        }
+        pivots.push(col);
     }
-    ret
+
+    m.truncate(pivots.len());
+    (m, pivots)
 }
 
-pub fn dot_vm<T: Mul<Output = T> + Copy + Default + AddAssign>(a: &[T], b: &[Vec<T>]) -> Vec<T> {
-    let alen = a.len();
+/// Reduced echelon form of the augmented matrix [A | b], None if A x = b is inconsistent.
+fn augmented_rref(a: &[Vec<i64>], b: &[i64]) -> Option<(Vec<Vec<i64>>, Vec<usize>)> {
+    assert_eq!(a.len(), b.len());
+    let cols = a.first().map_or(0, |row| row.len());
+    let augmented: Vec<Vec<i64>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, bi)| row.iter().copied().chain([*bi]).collect())
+        .collect();
+    let (m, pivots) = integer_rref(&augmented);
+    match pivots.last() {
+        Some(col) if *col == cols => None,
+        _ => Some((m, pivots)),
+    }
+}
 
-    let brows = b.len();
-    let bcols = b[0].len();
+/// Solve A x = b exactly over the rationals.
+///
+/// Returns the solution where all free variables are zero, None if there is no solution.
+/// All solutions are obtained by adding elements of the null space.
+pub fn solve_rational(a: &[Vec<i64>], b: &[i64]) -> Option<Vec<Rational<i64>>> {
+    let (m, pivots) = augmented_rref(a, b)?;
+    let cols = a.first().map_or(0, |row| row.len());
+    let mut x = vec![Rational::from_int(0); cols];
+    for (row, col) in m.iter().zip(pivots) {
+        x[col] = Rational::new(row[cols], row[col]);
+    }
+    Some(x)
+}
 
-    assert_eq!(alen, brows);
+/// Basis of the null space {x : A x = 0}, scaled to primitive integer vectors.
+///
+/// There is one vector per free variable, with a positive entry for that variable and
+/// zeros for the other free variables.
+pub fn integer_null_space(a: &[Vec<i64>]) -> Vec<Vec<i64>> {
+    let (m, pivots) = integer_rref(a);
+    let cols = a.first().map_or(0, |row| row.len());
+    (0..cols)
+        .filter(|col| !pivots.contains(col))
+        .map(|free| {
+            // x_free = L and x_pivot = -L row[free] / row[pivot] for L = lcm of the pivots
+            let l = m.iter().zip(pivots.iter()).fold(1, |acc, (row, p)| {
+                let pv = row[*p];
+                acc / gcd(acc, pv) * pv
+            });
+            let mut v = vec![0; cols];
+            v[free] = l;
+            for (row, p) in m.iter().zip(pivots.iter()) {
+                v[*p] = -l / row[*p] * row[free];
+            }
+            make_primitive(&mut v);
+            v
+        })
+        .collect()
+}
 
-    let mut ret = vec![T::default(); bcols];
-    (0..bcols).for_each(|i| {
-        (0..alen).for_each(|k| {
-            ret[i] += a[k] * b[k][i];
+/// Integer solution of A x = b with 0 <= x <= upper that minimises the sum of x.
+///
+/// Enumerates all values of the free variables of the echelon form within their bounds,
+/// so it is intended for small systems with few degrees of freedom.
+pub fn min_sum_nonneg_solution(a: &[Vec<i64>], b: &[i64], upper: &[i64]) -> Option<Vec<i64>> {
+    let (m, pivots) = augmented_rref(a, b)?;
+    let cols = a.first().map_or(0, |row| row.len());
+    assert_eq!(upper.len(), cols);
+    let free: Vec<usize> = (0..cols).filter(|c| !pivots.contains(c)).collect();
+
+    let mut best: Option<(i64, Vec<i64>)> = None;
+    let mut x = vec![0; cols];
+    loop {
+        // solve for the pivot variables given the free ones
+        let feasible = m.iter().zip(pivots.iter()).all(|(row, p)| {
+            let rhs = row[cols] - free.iter().map(|f| row[*f] * x[*f]).sum::<i64>();
+            x[*p] = rhs / row[*p];
+            rhs % row[*p] == 0 && (0..=upper[*p]).contains(&x[*p])
         });
-    });
-    ret
+        if feasible {
+            let sum = x.iter().sum::<i64>();
+            if best.as_ref().is_none_or(|(s, _)| sum < *s) {
+                best = Some((sum, x.clone()));
+            }
+        }
+
+        // advance free variables like an odometer
+        let Some(i) = free.iter().position(|f| x[*f] < upper[*f]) else {
+            break;
+        };
+        x[free[i]] += 1;
+        free[..i].iter().for_each(|f| x[*f] = 0);
+    }
+    best.map(|(_, x)| x)
 }
 
-pub fn dot_mv<T: Mul<Output = T> + Copy + Default + AddAssign>(a: &[Vec<T>], b: &[T]) -> Vec<T> {
-    let arows = a.len();
-    let acols = a[0].len();
+/// Value at x of the unique polynomial of lowest degree through the points (xi, yi).
+/// The xi must be distinct.
+pub fn lagrange_interpolate(points: &[(i64, i64)], x: i64) -> Rational<i64> {
+    let mut ret = Rational::zero();
+    for (i, (xi, yi)) in points.iter().enumerate() {
+        let mut term = Rational::from_int(*yi);
+        for (j, (xj, _)) in points.iter().enumerate() {
+            if i != j {
+                term *= Rational::new(x - xj, xi - xj);
+            }
+        }
+        ret += term;
+    }
+    ret
+}
 
-    let blen = b.len();
+/// Value at index n of a sequence that is polynomial in its index, by finite differences.
+///
+/// Returns None if the differences of the samples do not become constant, i.e. there are
+/// too few samples to determine the polynomial, or if the result does not fit in i64.
+pub fn extrapolate_differences(seq: &[i64], n: u64) -> Option<i64> {
+    // leading entries of the difference table, f(n) = sum_k C(n, k) diff^k f(0)
+    let mut leading = Vec::new();
+    let mut row: Vec<i128> = seq.iter().map(|x| *x as i128).collect();
+    while !row.windows(2).all(|w| w[0] == w[1]) {
+        leading.push(row[0]);
+        row = row.windows(2).map(|w| w[1] - w[0]).collect();
+    }
+    if row.len() < 2 {
+        return None;
+    }
+    leading.push(row[0]);
 
-    assert_eq!(acols, blen);
+    let (mut ret, mut binom) = (0i128, 1i128);
+    for (k, d) in leading.iter().enumerate() {
+        ret = ret.checked_add(binom.checked_mul(*d)?)?;
+        binom = binom.checked_mul(n as i128 - k as i128)? / (k as i128 + 1);
+    }
+    i64::try_from(ret).ok()
+}
 
-    let mut ret = vec![T::default(); arows];
-    (0..arows).for_each(|i| {
-        (0..acols).for_each(|k| {
-            ret[i] += a[i][k] * b[k];
-        });
-    });
-    ret
+/// Shortest linear recurrence s[i] = c[0] s[i-1] + ... + c[L-1] s[i-L] generating seq,
+/// by the Berlekamp–Massey algorithm over a field such as Rational or ModInt.
+///
+/// The recurrence is only trustworthy if seq has at least 2L elements.
+pub fn berlekamp_massey<T: Num + Copy>(seq: &[T]) -> Vec<T> {
+    // connection polynomial c(x) = 1 + c1 x + ... and the one before the last length change
+    let (mut c, mut b) = (vec![T::one()], vec![T::one()]);
+    let (mut len, mut shift, mut b_disc) = (0, 1, T::one());
+    for i in 0..seq.len() {
+        let disc = (1..=len).fold(seq[i], |acc, j| acc + c[j] * seq[i - j]);
+        if disc.is_zero() {
+            shift += 1;
+            continue;
+        }
+        let coef = disc / b_disc;
+        let prev = c.clone();
+        if c.len() < b.len() + shift {
+            c.resize(b.len() + shift, T::zero());
+        }
+        for (j, bj) in b.iter().enumerate() {
+            c[j + shift] = c[j + shift] - coef * *bj;
+        }
+        if 2 * len <= i {
+            (len, b, b_disc, shift) = (i + 1 - len, prev, disc, 1);
+        } else {
+            shift += 1;
+        }
+    }
+    c.resize(len + 1, T::zero());
+    c[1..].iter().map(|x| T::zero() - *x).collect()
 }
 
-pub fn dot<T: Mul<Output = T> + Copy + Default + Sum>(a: &[T], b: &[T]) -> T {
-    a.iter().zip(b.iter()).map(|(a, b)| *a * *b).sum::<T>()
+/// Value at index n of the sequence s[i] = c[0] s[i-1] + ... + c[L-1] s[i-L] that starts
+/// with init[0..L], using the companion matrix raised to the n-th power.
+/// Matrix entries grow with n, so use ModInt for large n.
+pub fn linear_recurrence_nth<T: Num + Copy + Default + AddAssign>(
+    c: &[T],
+    init: &[T],
+    n: u64,
+) -> T {
+    let l = c.len();
+    assert_eq!(init.len(), l);
+    if l == 0 {
+        // the empty recurrence generates the all-zero sequence
+        return T::zero();
+    }
+    if (n as usize) < l {
+        return init[n as usize];
+    }
+    // state (s[i+L-1], ..., s[i]) is advanced by one step by the companion matrix
+    let mut companion = Matrix::new(l, l);
+    for (j, cj) in c.iter().enumerate() {
+        companion[(0, j)] = *cj;
+    }
+    (1..l).for_each(|i| companion[(i, i - 1)] = T::one());
+    let state: Vec<T> = init.iter().rev().copied().collect();
+    companion.pow(n - l as u64 + 1).mul_vec(&state)[0]
 }
 
 #[cfg(test)]
@@ -267,6 +577,55 @@ mod tests {
         assert_eq!(number_length(999), 3);
         assert_eq!(number_length(1000), 4);
         assert_eq!(number_length(9999), 4);
+        assert_eq!(number_length(0), 1);
+        assert_eq!(number_length(-42), 2);
+        assert_eq!(number_length(u128::MAX), 39);
+    }
+
+    #[test]
+    fn test_number_length_brute_force() {
+        for x in 0..20_000u32 {
+            assert_eq!(number_length(x), x.to_string().len() as u64);
+        }
+    }
+
+    #[test]
+    fn test_gcd_lcm_brute_force() {
+        for n in 0..60u64 {
+            for m in 0..60u64 {
+                let g = (1..=n.max(m))
+                    .filter(|d| n % d == 0 && m % d == 0)
+                    .max()
+                    .unwrap_or(0);
+                assert_eq!(gcd(n, m), g);
+                assert_eq!(gcd(n as u32, m as u32), g as u32);
+                assert_eq!(gcd(n as u128, m as u128), g as u128);
+                assert_eq!(gcd(-(n as i64), m as i64), g as i64);
+
+                let l = match n * m {
+                    0 => 0,
+                    _ => (1..=n * m).find(|k| k % n == 0 && k % m == 0).unwrap(),
+                };
+                assert_eq!(lcm(n, m), l);
+                assert_eq!(checked_lcm(n as i8, -(m as i8)), i8::try_from(l).ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_checked_lcm() {
+        assert_eq!(checked_lcm(1u64 << 40, 3 << 30), Some(3 << 40));
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(checked_lcm(1u128 << 100, 3), Some(3 << 100));
+        assert_eq!(checked_lcm(0u32, 0), Some(0));
+    }
+
+    #[test]
+    fn test_bezout_generic() {
+        let (x, y) = bezout(1027i32, 712).unwrap();
+        assert_eq!(1027 * x + 712 * y, 1);
+        let (x, y) = bezout(-(1i128 << 80) - 1, 1 << 70).unwrap();
+        assert_eq!((-(1i128 << 80) - 1) * x + (1 << 70) * y, 1);
     }
 
     #[test]
@@ -412,6 +771,92 @@ mod tests {
         assert_eq!(crt(&ns, &aa), None);
     }
 
+    #[test]
+    fn test_crt2_non_coprime() {
+        // x = 3 (mod 4), x = 5 (mod 6) has the solution 11 (mod 12)
+        assert_eq!(crt2(4, 3, 6, 5), Some(11));
+        assert_eq!(crt2(4, 3, 6, 4), None);
+        assert_eq!(crt(&[4, 6, 9], &[3, 5, 2]), Some(11));
+        assert_eq!(
+            crt2(1_000_000_007, 5, 998_244_353, 7),
+            Some(988_413_467_918_894_232)
+        );
+    }
+
+    #[test]
+    fn test_crt_brute_force() {
+        for n1 in 1..13i64 {
+            for n2 in 1..13 {
+                for a1 in 0..n1 {
+                    for a2 in -n2..n2 {
+                        let l = lcm(n1, n2);
+                        let x =
+                            (0..l).find(|x| x % n1 == a1 && x.rem_euclid(n2) == a2.rem_euclid(n2));
+                        assert_eq!(crt2(n1, a1, n2, a2), x);
+                        assert_eq!(checked_crt(&[n1, n2], &[a1, a2]), x.map(|x| (x, l)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_checked_crt_overflow() {
+        // product of the moduli overflows i64 but the lcm does not
+        let (p, q) = (1_000_000_007, 998_244_353);
+        let x = 123_456_789_123_456_789;
+        let ns = [p * 3, q * 3, 9];
+        let aa = ns.map(|n| x % n);
+        assert_eq!(checked_crt(&ns, &aa), Some((x, p * q * 9)));
+
+        // the lcm of co-prime moduli overflows i64
+        let ns = [p, q, 1_000_000_009];
+        assert_eq!(checked_crt(&ns, &[1, 2, 3]), None);
+        assert_eq!(crt(&ns, &[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn test_pow_mod() {
+        assert_eq!(pow_mod(2, 10, 1000), 24);
+        assert_eq!(pow_mod(3, 0, 7), 1);
+        assert_eq!(pow_mod(3, 0, 1), 0);
+        assert_eq!(pow_mod(u64::MAX, 2, u64::MAX - 1), 1);
+    }
+
+    #[test]
+    fn test_primes() {
+        assert_eq!(primes_up_to(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(primes_up_to(1), vec![]);
+        assert_eq!(primes_up_to(2), vec![2]);
+        assert_eq!(primes_up_to(100_000).len(), 9592);
+    }
+
+    #[test]
+    fn test_factorize() {
+        assert_eq!(factorize(1), vec![]);
+        assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(97), vec![(97, 1)]);
+        assert_eq!(
+            factorize(600851475143),
+            vec![(71, 1), (839, 1), (1471, 1), (6857, 1)]
+        );
+        for n in 1..500u64 {
+            let prod: u64 = factorize(n).iter().map(|(p, e)| p.pow(*e)).product();
+            assert_eq!(prod, n);
+        }
+    }
+
+    #[test]
+    fn test_totient() {
+        assert_eq!(totient(1), 1);
+        assert_eq!(totient(9), 6);
+        assert_eq!(totient(36), 12);
+        for n in 1..200u64 {
+            let count = (1..=n).filter(|k| gcd(*k, n) == 1).count() as u64;
+            assert_eq!(totient(n), count);
+        }
+    }
+
     #[test]
     fn test_nchoose_k() {
         let mut it = NChooseK::new(4, 2);
@@ -443,4 +888,148 @@ mod tests {
         let c = nchoosek_iter(3, 2).collect::<Vec<_>>();
         assert_eq!(c, vec![vec![0, 1], vec![0, 2], vec![1, 2],]);
     }
+
+    #[test]
+    fn test_integer_rref() {
+        let a = vec![vec![2, 4, 6], vec![1, 3, 5], vec![3, 7, 11]];
+        let (m, pivots) = integer_rref(&a);
+        assert_eq!(pivots, vec![0, 1]);
+        assert_eq!(m, vec![vec![1, 0, -1], vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_solve_rational() {
+        // 2x + y = 1, x + 3y = 2  =>  x = 1/5, y = 3/5
+        let a = vec![vec![2, 1], vec![1, 3]];
+        let x = solve_rational(&a, &[1, 2]).unwrap();
+        assert_eq!(x, vec![Rational::new(1, 5), Rational::new(3, 5)]);
+
+        // inconsistent
+        let a = vec![vec![1, 1], vec![2, 2]];
+        assert_eq!(solve_rational(&a, &[1, 3]), None);
+    }
+
+    #[test]
+    fn test_integer_null_space() {
+        let a = vec![vec![2, 4, 6], vec![1, 3, 5]];
+        assert_eq!(integer_null_space(&a), vec![vec![1, -2, 1]]);
+
+        let a = vec![vec![2, 3, 0, 0]];
+        let ns = integer_null_space(&a);
+        assert_eq!(
+            ns,
+            vec![vec![-3, 2, 0, 0], vec![0, 0, 1, 0], vec![0, 0, 0, 1]]
+        );
+        assert_eq!(integer_null_space(&[vec![1, 0], vec![0, 1]]).len(), 0);
+    }
+
+    #[test]
+    fn test_min_sum_nonneg_solution() {
+        // first machine of 2025 day 10, one column per button
+        let a = vec![
+            vec![0, 0, 0, 0, 1, 1],
+            vec![0, 1, 0, 0, 0, 1],
+            vec![0, 0, 1, 1, 1, 0],
+            vec![1, 1, 0, 1, 0, 0],
+        ];
+        let b = [3, 5, 4, 7];
+        let x = min_sum_nonneg_solution(&a, &b, &[7; 6]).unwrap();
+        assert_eq!(x.iter().sum::<i64>(), 10);
+        assert_eq!(dot_mv(&a, &x), b);
+
+        // 2x + 2y = 3 has no integer solution
+        assert_eq!(min_sum_nonneg_solution(&[vec![2, 2]], &[3], &[5, 5]), None);
+    }
+
+    #[test]
+    fn test_lagrange_interpolate() {
+        // y = x^2 - 3x + 1
+        let pts = [(0, 1), (1, -1), (3, 1)];
+        assert_eq!(lagrange_interpolate(&pts, 5), Rational::from_int(11));
+        assert_eq!(lagrange_interpolate(&pts, 1), Rational::from_int(-1));
+        // y = x / 2 at a non-integer value
+        assert_eq!(
+            lagrange_interpolate(&[(0, 0), (2, 1)], 3),
+            Rational::new(3, 2)
+        );
+        // sampled at 65 + 131 k as in 2023 day 21
+        let f = |x: i64| 14881 * x * x + 14958 * x + 3802;
+        let pts = [0, 1, 2].map(|k| (k, f(k)));
+        let k = (26501365 - 65) / 131;
+        assert_eq!(lagrange_interpolate(&pts, k).to_integer(), Some(f(k)));
+    }
+
+    #[test]
+    fn test_extrapolate_differences() {
+        // examples from 2023 day 9
+        assert_eq!(extrapolate_differences(&[0, 3, 6, 9, 12, 15], 6), Some(18));
+        assert_eq!(extrapolate_differences(&[1, 3, 6, 10, 15, 21], 6), Some(28));
+        assert_eq!(
+            extrapolate_differences(&[10, 13, 16, 21, 30, 45], 6),
+            Some(68)
+        );
+        // interpolation inside the samples and far away
+        assert_eq!(extrapolate_differences(&[1, 4, 9, 16], 1), Some(4));
+        assert_eq!(
+            extrapolate_differences(&[0, 1, 4, 9], 26_000_000),
+            Some(676_000_000_000_000)
+        );
+        assert_eq!(extrapolate_differences(&[5, 5], 100), Some(5));
+        // not enough samples to see constant differences
+        assert_eq!(extrapolate_differences(&[1, 2, 4, 8, 16], 5), None);
+        assert_eq!(extrapolate_differences(&[7], 1), None);
+        // overflow
+        assert_eq!(
+            extrapolate_differences(&[0, 1, 8, 27, 64], u32::MAX as u64 * 2),
+            None
+        );
+    }
+
+    #[test]
+    fn test_berlekamp_massey() {
+        type Q = Rational<i64>;
+        let q = |v: &[i64]| v.iter().map(|x| Q::from_int(*x)).collect::<Vec<_>>();
+
+        let fib = q(&[1, 1, 2, 3, 5, 8, 13, 21]);
+        assert_eq!(berlekamp_massey(&fib), q(&[1, 1]));
+
+        let squares: Vec<Q> = (0..10).map(|i| Q::from_int(i * i)).collect();
+        assert_eq!(berlekamp_massey(&squares), q(&[3, -3, 1]));
+
+        let geometric = q(&[3, 6, 12, 24]);
+        assert_eq!(berlekamp_massey(&geometric), q(&[2]));
+        assert_eq!(berlekamp_massey(&q(&[0, 0, 0])), vec![]);
+
+        // s[i] = 2 s[i-1] - s[i-3] + 5 s[i-4] modulo a prime
+        type F = crate::modint::ModInt1e9_7;
+        let c = [2, 0, -1, 5].map(F::from_i64);
+        let mut s = [1, 2, 3, 4].map(F::new).to_vec();
+        for i in 4..20 {
+            let next = (0..4).map(|j| c[j] * s[i - 1 - j]).sum();
+            s.push(next);
+        }
+        assert_eq!(berlekamp_massey(&s), c.to_vec());
+    }
+
+    #[test]
+    fn test_linear_recurrence_nth() {
+        let (c, init) = ([1, 1], [0, 1]);
+        let fib: Vec<i64> = (0..10)
+            .map(|n| linear_recurrence_nth(&c, &init, n))
+            .collect();
+        assert_eq!(fib, vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+        assert_eq!(linear_recurrence_nth(&c, &init, 90), 2880067194370816120);
+
+        // recover the recurrence of a sampled sequence and jump far ahead
+        type Q = Rational<i64>;
+        let samples: Vec<Q> = (0..8).map(|i| Q::from_int(i * i + 2 * i)).collect();
+        let c = berlekamp_massey(&samples);
+        let n = linear_recurrence_nth(&c, &samples[..c.len()], 10_000);
+        assert_eq!(n, Q::from_int(100_020_000));
+
+        // an all-zero sequence has the empty recurrence
+        let zeros = [Q::zero(); 4];
+        let c = berlekamp_massey(&zeros);
+        assert_eq!(linear_recurrence_nth(&c, &zeros[..c.len()], 10), Q::zero());
+    }
 }
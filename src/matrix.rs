@@ -0,0 +1,216 @@
+use num_traits::One;
+use std::ops::{AddAssign, Index, IndexMut, Mul};
+
+/// Dense row-major matrix with dimensions known at runtime.
+///
+/// Elements only need to support + and *, so integers, rational::Rational and
+/// modint::ModInt all work; the latter gives matrix arithmetic modulo a constant.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: Copy + Default + AddAssign + Mul<Output = T>> Matrix<T> {
+    /// Matrix filled with T::default().
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![T::default(); rows * cols],
+        }
+    }
+
+    /// Create from a slice of equally long rows. Panics if the rows are ragged.
+    pub fn from_rows(rows: &[Vec<T>]) -> Self {
+        let cols = rows.first().map_or(0, |row| row.len());
+        assert!(rows.iter().all(|row| row.len() == cols), "ragged rows");
+        Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.concat(),
+        }
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<T>> {
+        (0..self.rows).map(|i| self.row(i).to_vec()).collect()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut ret = Matrix::new(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                ret[(j, i)] = self[(i, j)];
+            }
+        }
+        ret
+    }
+
+    /// Matrix-vector product self * v.
+    pub fn mul_vec(&self, v: &[T]) -> Vec<T> {
+        assert_eq!(self.cols, v.len());
+        (0..self.rows)
+            .map(|i| {
+                let mut acc = T::default();
+                for (a, b) in self.row(i).iter().zip(v) {
+                    acc += *a * *b;
+                }
+                acc
+            })
+            .collect()
+    }
+
+    /// Vector-matrix product v * self.
+    pub fn vec_mul(&self, v: &[T]) -> Vec<T> {
+        assert_eq!(self.rows, v.len());
+        let mut ret = vec![T::default(); self.cols];
+        for (i, x) in v.iter().enumerate() {
+            for (r, a) in ret.iter_mut().zip(self.row(i)) {
+                *r += *x * *a;
+            }
+        }
+        ret
+    }
+}
+
+impl<T: Copy + Default + AddAssign + Mul<Output = T> + One> Matrix<T> {
+    pub fn identity(n: usize) -> Self {
+        let mut ret = Matrix::new(n, n);
+        (0..n).for_each(|i| ret[(i, i)] = T::one());
+        ret
+    }
+
+    /// self^exp by repeated squaring. Panics if the matrix is not square.
+    pub fn pow(&self, mut exp: u64) -> Self {
+        assert_eq!(self.rows, self.cols, "matrix must be square");
+        let mut base = self.clone();
+        let mut ret = Matrix::identity(self.rows);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                ret = &ret * &base;
+            }
+            exp >>= 1;
+            // skip the last squaring, it may overflow even if the result does not
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        ret
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        assert!(j < self.cols, "column {j} out of range");
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        assert!(j < self.cols, "column {j} out of range");
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl<T: Copy + Default + AddAssign + Mul<Output = T>> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(self, rhs: Self) -> Self::Output {
+        assert_eq!(self.cols, rhs.rows);
+        let mut ret = Matrix::new(self.rows, rhs.cols);
+        for i in 0..self.rows {
+            for (k, a) in self.row(i).iter().enumerate() {
+                // i-k-j order walks both matrices row by row
+                for (r, b) in ret.data[i * rhs.cols..(i + 1) * rhs.cols]
+                    .iter_mut()
+                    .zip(rhs.row(k))
+                {
+                    *r += *a * *b;
+                }
+            }
+        }
+        ret
+    }
+}
+
+impl<T: Copy + Default + AddAssign + Mul<Output = T>> Mul for Matrix<T> {
+    type Output = Matrix<T>;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::ModInt1e9_7;
+
+    #[test]
+    fn test_matrix_basics() {
+        let a = Matrix::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!((a.rows(), a.cols()), (2, 3));
+        assert_eq!(a[(1, 0)], 4);
+        assert_eq!(a.row(1), &[4, 5, 6]);
+        assert_eq!(
+            a.transpose().to_rows(),
+            vec![vec![1, 4], vec![2, 5], vec![3, 6]]
+        );
+        assert_eq!(a.mul_vec(&[1, 0, -1]), vec![-2, -2]);
+        assert_eq!(a.vec_mul(&[1, -1]), vec![-3, -3, -3]);
+    }
+
+    #[test]
+    fn test_matrix_mul() {
+        let a = Matrix::from_rows(&[vec![1, 2], vec![3, 4], vec![5, 6]]);
+        let b = Matrix::from_rows(&[vec![1, 0, 2], vec![0, 1, 3]]);
+        assert_eq!(
+            (&a * &b).to_rows(),
+            vec![vec![1, 2, 8], vec![3, 4, 18], vec![5, 6, 28]]
+        );
+        assert_eq!((&b * &a).to_rows(), vec![vec![11, 14], vec![18, 22]]);
+        assert_eq!(&a * &Matrix::identity(2), a);
+    }
+
+    #[test]
+    fn test_matrix_pow() {
+        // fibonacci numbers as a linear recurrence
+        let fib = Matrix::from_rows(&[vec![1u64, 1], vec![1, 0]]);
+        assert_eq!(fib.pow(0), Matrix::identity(2));
+        assert_eq!(fib.pow(10)[(0, 1)], 55);
+        assert_eq!(fib.pow(90)[(0, 1)], 2880067194370816120);
+
+        // and modulo a prime
+        let one = ModInt1e9_7::new(1);
+        let fib = Matrix::from_rows(&[vec![one, one], vec![one, ModInt1e9_7::new(0)]]);
+        assert_eq!(fib.pow(1_000_000_000_000)[(0, 1)].value(), 730695249);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_matrix_index_out_of_range() {
+        // would otherwise read a[(1, 0)]
+        let a = Matrix::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]]);
+        let _ = a[(0, 3)];
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_matrix_mul_dimension_mismatch() {
+        let a = Matrix::from_rows(&[vec![1, 2]]);
+        let _ = &a * &a;
+    }
+}
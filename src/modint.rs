@@ -0,0 +1,233 @@
+use crate::math::{bezout, pow_mod};
+use crate::vector::forward_ref_ops;
+use num_traits::{Num, One, Zero};
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+/// Integer modulo the compile time constant M, always stored reduced to 0..M.
+///
+/// Products, reductions and inverses are computed in 128 bits, so any modulus up to
+/// u64::MAX is safe. Division multiplies by the inverse from math::bezout and panics
+/// if the divisor shares a factor with M, which can only happen for composite M.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Default)]
+pub struct ModInt<const M: u64> {
+    val: u64,
+}
+
+/// Commonly used prime modulus.
+pub type ModInt1e9_7 = ModInt<1_000_000_007>;
+
+impl<const M: u64> ModInt<M> {
+    pub const MODULUS: u64 = M;
+
+    pub fn new(val: u64) -> Self {
+        ModInt { val: val % M }
+    }
+
+    /// Create from a possibly negative integer.
+    pub fn from_i64(val: i64) -> Self {
+        ModInt {
+            val: (val as i128).rem_euclid(M as i128) as u64,
+        }
+    }
+
+    /// Representative in 0..M.
+    pub fn value(&self) -> u64 {
+        self.val
+    }
+
+    /// self^exp by repeated squaring.
+    pub fn pow(&self, exp: u64) -> Self {
+        ModInt {
+            val: pow_mod(self.val, exp, M),
+        }
+    }
+
+    /// Multiplicative inverse, if self and M are co-prime.
+    pub fn inv(&self) -> Option<Self> {
+        let (x, _) = bezout(self.val as i128, M as i128)?;
+        Some(ModInt {
+            val: x.rem_euclid(M as i128) as u64,
+        })
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(val: u64) -> Self {
+        ModInt::new(val)
+    }
+}
+
+impl<const M: u64> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.val)
+    }
+}
+
+impl<const M: u64> Zero for ModInt<M> {
+    fn zero() -> Self {
+        ModInt { val: 0 }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.val == 0
+    }
+}
+
+impl<const M: u64> One for ModInt<M> {
+    fn one() -> Self {
+        ModInt::new(1)
+    }
+}
+
+impl<const M: u64> Num for ModInt<M> {
+    type FromStrRadixErr = std::num::ParseIntError;
+
+    /// Parse a possibly negative integer and reduce it.
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        let val = i128::from_str_radix(s.trim(), radix)?;
+        Ok(ModInt {
+            val: val.rem_euclid(M as i128) as u64,
+        })
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        ModInt::new(M - self.val)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        ModInt {
+            val: ((self.val as u128 + rhs.val as u128) % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        ModInt {
+            val: (self.val as u128 * rhs.val as u128 % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> Div for ModInt<M> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        self.mul(rhs.inv().expect("divisor not invertible"))
+    }
+}
+
+/// Division by an invertible element is exact, so the remainder is zero.
+/// Like Div, panics if rhs is not invertible.
+impl<const M: u64> Rem for ModInt<M> {
+    type Output = Self;
+    fn rem(self, rhs: Self) -> Self::Output {
+        assert!(rhs.inv().is_some(), "divisor not invertible");
+        ModInt::zero()
+    }
+}
+
+impl<const M: u64> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(ModInt::zero(), |acc, x| acc + x)
+    }
+}
+
+impl<const M: u64> Product for ModInt<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(ModInt::one(), |acc, x| acc * x)
+    }
+}
+
+forward_ref_ops!(impl[const M: u64] ModInt<M>;
+    (Add add AddAssign add_assign)
+    (Sub sub SubAssign sub_assign)
+    (Mul mul MulAssign mul_assign)
+    (Div div DivAssign div_assign)
+    (Rem rem RemAssign rem_assign)
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::dot;
+
+    type F7 = ModInt<7>;
+
+    #[test]
+    fn test_modint_ops() {
+        let (a, b) = (F7::new(5), F7::new(4));
+        assert_eq!(a + b, F7::new(2));
+        assert_eq!(a - b, F7::new(1));
+        assert_eq!(b - a, F7::new(6));
+        assert_eq!(a * b, F7::new(6));
+        assert_eq!(a / b * b, a);
+        assert_eq!(-F7::zero(), F7::zero());
+        assert_eq!(F7::from_i64(-1), F7::new(6));
+        assert_eq!(F7::new(15).value(), 1);
+
+        let mut x = a;
+        x += b;
+        x *= &b;
+        assert_eq!(x, F7::new(1));
+        assert_eq!(F7::from_str_radix("-8", 10).unwrap(), F7::new(6));
+    }
+
+    #[test]
+    fn test_modint_pow_inv() {
+        assert_eq!(F7::new(3).pow(6), F7::one());
+        assert_eq!(F7::new(3).inv(), Some(F7::new(5)));
+        assert_eq!(F7::zero().inv(), None);
+        assert_eq!(ModInt::<12>::new(4).inv(), None);
+        assert_eq!(ModInt::<12>::new(5).inv(), Some(ModInt::new(5)));
+
+        // Fermat's little theorem for a large prime modulus
+        let x = ModInt1e9_7::new(123_456_789);
+        assert_eq!(x.pow(ModInt1e9_7::MODULUS - 1), ModInt::one());
+        assert_eq!(x.inv(), Some(x.pow(ModInt1e9_7::MODULUS - 2)));
+
+        // largest prime below 2^64, well above i64::MAX
+        type Big = ModInt<18_446_744_073_709_551_557>;
+        assert_eq!(Big::from_i64(5).value(), 5);
+        assert_eq!(Big::from_i64(-1).value(), Big::MODULUS - 1);
+        let y = Big::new(u64::MAX);
+        assert_eq!(y * y.inv().unwrap(), Big::one());
+        assert_eq!(y.inv(), Some(y.pow(Big::MODULUS - 2)));
+        assert_eq!(ModInt::<{ u64::MAX }>::new(3).inv(), None);
+    }
+
+    #[test]
+    fn test_modint_rem() {
+        assert_eq!(F7::new(5) % F7::new(3), F7::zero());
+        assert_eq!(ModInt::<12>::new(4) % ModInt::new(5), ModInt::zero());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_modint_rem_not_invertible() {
+        let _ = ModInt::<12>::new(4) % ModInt::new(2);
+    }
+
+    #[test]
+    fn test_modint_linear_algebra() {
+        let a = [F7::new(1), F7::new(2), F7::new(3)];
+        let b = [F7::new(4), F7::new(5), F7::new(6)];
+        assert_eq!(dot(&a, &b), F7::new(32 % 7));
+        assert_eq!((1..7).map(F7::new).product::<F7>(), F7::new(6));
+    }
+}
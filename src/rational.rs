@@ -0,0 +1,297 @@
+use crate::math::gcd;
+use crate::vector::forward_ref_ops;
+use num_traits::{Num, One, PrimInt, Signed, Zero};
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+/// Exact fraction num / den, always stored in lowest terms with den > 0.
+///
+/// Implements the num-traits operations, so it satisfies vector::Scalar and can be used
+/// in Vector and the math::gemm / math::dot helpers.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Rational<T: PrimInt + Signed> {
+    num: T,
+    den: T,
+}
+
+impl<T: PrimInt + Signed> Rational<T> {
+    /// Create num / den in lowest terms. Panics if den is zero.
+    pub fn new(num: T, den: T) -> Self {
+        assert!(!den.is_zero(), "zero denominator");
+        let g = gcd(num, den) * den.signum();
+        Rational {
+            num: num / g,
+            den: den / g,
+        }
+    }
+
+    /// Create integer n / 1.
+    pub fn from_int(n: T) -> Self {
+        Rational {
+            num: n,
+            den: T::one(),
+        }
+    }
+
+    pub fn numer(&self) -> T {
+        self.num
+    }
+
+    pub fn denom(&self) -> T {
+        self.den
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den.is_one()
+    }
+
+    /// Integer value if the fraction is an integer.
+    pub fn to_integer(&self) -> Option<T> {
+        self.is_integer().then_some(self.num)
+    }
+
+    /// Largest integer not greater than self.
+    pub fn floor(&self) -> T {
+        let q = self.num / self.den;
+        match self.num % self.den < T::zero() {
+            true => q - T::one(),
+            false => q,
+        }
+    }
+
+    /// Smallest integer not less than self.
+    pub fn ceil(&self) -> T {
+        -(-*self).floor()
+    }
+
+    /// Multiplicative inverse. Panics if self is zero.
+    pub fn recip(&self) -> Self {
+        Rational::new(self.den, self.num)
+    }
+}
+
+impl<T: PrimInt + Signed> From<T> for Rational<T> {
+    fn from(n: T) -> Self {
+        Rational::from_int(n)
+    }
+}
+
+impl<T: PrimInt + Signed> Default for Rational<T> {
+    fn default() -> Self {
+        Rational::zero()
+    }
+}
+
+impl<T: PrimInt + Signed> PartialOrd for Rational<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PrimInt + Signed> Ord for Rational<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // denominators are positive, so cross multiplication preserves order
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl<T: PrimInt + Signed + fmt::Display> fmt::Display for Rational<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.is_integer() {
+            true => write!(f, "{}", self.num),
+            false => write!(f, "{}/{}", self.num, self.den),
+        }
+    }
+}
+
+impl<T: PrimInt + Signed> Zero for Rational<T> {
+    fn zero() -> Self {
+        Rational::from_int(T::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+}
+
+impl<T: PrimInt + Signed> One for Rational<T> {
+    fn one() -> Self {
+        Rational::from_int(T::one())
+    }
+}
+
+impl<T: PrimInt + Signed> Num for Rational<T> {
+    type FromStrRadixErr = String;
+
+    /// Parse "num/den" or "num".
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        let parse = |x: &str| {
+            T::from_str_radix(x.trim(), radix).map_err(|_| format!("invalid number '{x}'"))
+        };
+        match s.split_once('/') {
+            Some((num, den)) => {
+                let den = parse(den)?;
+                if den.is_zero() {
+                    return Err("zero denominator".to_string());
+                }
+                Ok(Rational::new(parse(num)?, den))
+            }
+            None => Ok(Rational::from_int(parse(s)?)),
+        }
+    }
+}
+
+impl<T: PrimInt + Signed> Neg for Rational<T> {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Rational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl<T: PrimInt + Signed> Add for Rational<T> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        // work with the lcm of the denominators to delay overflow
+        let g = gcd(self.den, rhs.den);
+        let num = self.num * (rhs.den / g) + rhs.num * (self.den / g);
+        Rational::new(num, self.den / g * rhs.den)
+    }
+}
+
+impl<T: PrimInt + Signed> Sub for Rational<T> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl<T: PrimInt + Signed> Mul for Rational<T> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        // cancel common factors before multiplying to delay overflow
+        let g1 = gcd(self.num, rhs.den);
+        let g2 = gcd(rhs.num, self.den);
+        Rational::new(
+            (self.num / g1) * (rhs.num / g2),
+            (self.den / g2) * (rhs.den / g1),
+        )
+    }
+}
+
+impl<T: PrimInt + Signed> Div for Rational<T> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        self.mul(rhs.recip())
+    }
+}
+
+/// Remainder of truncated division, self - rhs * trunc(self / rhs), as for integers.
+impl<T: PrimInt + Signed> Rem for Rational<T> {
+    type Output = Self;
+    fn rem(self, rhs: Self) -> Self::Output {
+        let q = self / rhs;
+        self - rhs * Rational::from_int(q.num / q.den)
+    }
+}
+
+impl<T: PrimInt + Signed> Sum for Rational<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Rational::zero(), |acc, x| acc + x)
+    }
+}
+
+impl<T: PrimInt + Signed> Product for Rational<T> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Rational::one(), |acc, x| acc * x)
+    }
+}
+
+forward_ref_ops!(impl[T: PrimInt + Signed] Rational<T>;
+    (Add add AddAssign add_assign)
+    (Sub sub SubAssign sub_assign)
+    (Mul mul MulAssign mul_assign)
+    (Div div DivAssign div_assign)
+    (Rem rem RemAssign rem_assign)
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{dot, gemm};
+    use crate::vector::Vec2;
+
+    type Q = Rational<i64>;
+
+    fn q(num: i64, den: i64) -> Q {
+        Rational::new(num, den)
+    }
+
+    #[test]
+    fn test_rational_normalise() {
+        assert_eq!(q(6, -4), q(-3, 2));
+        assert_eq!(q(6, -4).numer(), -3);
+        assert_eq!(q(6, -4).denom(), 2);
+        assert_eq!(q(0, -5), Q::zero());
+        assert!(q(8, 4).is_integer());
+        assert_eq!(q(8, 4).to_integer(), Some(2));
+        assert_eq!(q(7, 4).to_integer(), None);
+    }
+
+    #[test]
+    fn test_rational_ops() {
+        assert_eq!(q(1, 2) + q(1, 3), q(5, 6));
+        assert_eq!(q(1, 2) - q(1, 3), q(1, 6));
+        assert_eq!(q(2, 3) * q(9, 4), q(3, 2));
+        assert_eq!(q(2, 3) / q(4, 9), q(3, 2));
+        assert_eq!(q(7, 2) % q(1, 1), q(1, 2));
+        assert_eq!(q(-7, 2) % q(1, 1), q(-1, 2));
+        assert_eq!(-q(1, 2), q(-1, 2));
+
+        let mut x = q(1, 2);
+        x += q(1, 4);
+        x *= &q(4, 1);
+        assert_eq!(x, q(3, 1));
+        assert_eq!([q(1, 2), q(1, 3), q(1, 6)].into_iter().sum::<Q>(), Q::one());
+    }
+
+    #[test]
+    fn test_rational_order() {
+        assert!(q(1, 3) < q(1, 2));
+        assert!(q(-1, 2) < q(-1, 3));
+        assert_eq!(q(7, 2).floor(), 3);
+        assert_eq!(q(-7, 2).floor(), -4);
+        assert_eq!(q(-7, 2).ceil(), -3);
+        assert_eq!(q(4, 2).ceil(), 2);
+    }
+
+    #[test]
+    fn test_rational_parse_display() {
+        assert_eq!(Q::from_str_radix("-6/4", 10), Ok(q(-3, 2)));
+        assert_eq!(Q::from_str_radix("5", 10), Ok(q(5, 1)));
+        assert!(Q::from_str_radix("1/0", 10).is_err());
+        assert_eq!(q(-3, 2).to_string(), "-3/2");
+        assert_eq!(q(4, 2).to_string(), "2");
+    }
+
+    #[test]
+    fn test_rational_linear_algebra() {
+        let v = Vec2::new(q(1, 2), q(1, 3));
+        assert_eq!(v * q(6, 1), Vec2::new(q(3, 1), q(2, 1)));
+        assert_eq!(v.norm_sq(), q(13, 36));
+
+        // [2 1; 1 3]^-1 = 1/5 [3 -1; -1 2]
+        let a = vec![vec![q(2, 1), q(1, 1)], vec![q(1, 1), q(3, 1)]];
+        let a_inv = vec![vec![q(3, 5), q(-1, 5)], vec![q(-1, 5), q(2, 5)]];
+        let eye = gemm(&a, &a_inv);
+        assert_eq!(
+            eye,
+            vec![vec![Q::one(), Q::zero()], vec![Q::zero(), Q::one()]]
+        );
+        assert_eq!(dot(&a[0], &a_inv[0]), q(1, 1));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::bitmap2d::BitMap2d;
 use crate::dir::Dir;
 use crate::map2d::Map;
 use crate::vector::Vec2i;
@@ -67,10 +68,10 @@ pub fn part_a(input: &str) -> Answer {
     let (state_inner, map) = parse_board(&chars);
     let mut state: Option<State> = Some(state_inner);
 
-    let mut visited = BitVec::from_elem(130 * 130, false);
+    let mut visited = BitMap2d::new(map.h, map.w);
 
     while let Some((pos, _)) = state {
-        visited.set(pos.linear_idx(130), true);
+        visited.set(&pos, true);
         state = state.and_then(|s| step(&s, &map, None));
     }
 
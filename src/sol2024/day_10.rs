@@ -1,14 +1,14 @@
+use crate::bitmap2d::BitMap2d;
 use crate::dir::Dir;
 use crate::map2d::Map;
 use crate::vector::Vec2i;
 use crate::Answer;
-use bit_vec::BitVec;
 
 const DIRECTIONS: [Dir; 4] = [Dir::N, Dir::E, Dir::S, Dir::W];
 
 fn n_peaks_from_trailhead<const PARTB: bool>(trail_head: Vec2i, map: &Map<u32>) -> i64 {
     let mut stack = vec![trail_head];
-    let mut peaks = BitVec::from_elem(map.h * map.w, false);
+    let mut peaks = BitMap2d::new(map.h, map.w);
     let mut n_dist = 0;
 
     while let Some(cur) = stack.pop() {
@@ -17,7 +17,7 @@ fn n_peaks_from_trailhead<const PARTB: bool>(trail_head: Vec2i, map: &Map<u32>)
                 if PARTB {
                     n_dist += 1;
                 } else {
-                    peaks.set(cur.linear_idx(map.w), true);
+                    peaks.set(&cur, true);
                 }
             }
             d => {
@@ -1,4 +1,5 @@
-use crate::math::diophantine;
+use crate::math::{diophantine, solve_rational};
+use crate::rational::Rational;
 use crate::{vector::Vec2i, Answer};
 
 #[derive(Debug, PartialEq)]
@@ -40,44 +41,23 @@ pub fn part_a(input: &str) -> Answer {
 fn solve_problem(problem: &Problem) -> Option<i64> {
     // Problem
     //    na * [ax; ay] + nb * [bx; by] = [cx; cy]
-    // can be expressed by a system of Diophantine equations
+    // is a linear system
     //    [ax bx] [na]  =  [cx]
     //    [ay by] [nb]     [cy]
-
-    // find all solutions (na + k u, nb0 + k v) of equation in x
-    let (na0, nb0, u, v) = diophantine(
-        problem.button_a.x(),
-        problem.button_b.x(),
-        problem.price.x(),
-    )?;
-
-    // substitute solutions into y equation
-    // ay na + by nb = cy
-    // ay (na0 + k u) + by (nb0 + k v) = cy
-    // ay na0 + ay u k + by nb0 + by v k = cy
-    // (ay u + by v) k = cy - ay na0 - by nb0
-    let ay = problem.button_a.y();
-    let by = problem.button_b.y();
-    let k_num = problem.price.y() - ay * na0 - by * nb0;
-    let k_denom = ay * u + by * v;
-
-    match k_denom {
-        0 => {
-            // infinite solutions, assuming this doesn't happen
-            unreachable!("Assuming single solution");
-        }
-        kd if k_num % k_denom == 0 => {
-            // one solution
-            let k = k_num / kd;
-            let x = na0 + k * u;
-            let y = nb0 + k * v;
-            Some(3 * x + y)
-        }
-        _ => {
-            // no solutions
-            None
-        }
+    // which we solve exactly with Cramer's rule and then check for integrality.
+    let (a, b, c) = (problem.button_a, problem.button_b, problem.price);
+    let det = a.x() * b.y() - b.x() * a.y();
+    if det == 0 {
+        // parallel buttons, the prize may still be out of reach
+        solve_rational(&[vec![a.x(), b.x()], vec![a.y(), b.y()]], &[c.x(), c.y()])?;
+        diophantine(a.x(), b.x(), c.x())?;
+        // infinite solutions, assuming this doesn't happen
+        unreachable!("Assuming single solution");
     }
+
+    let na = Rational::new(c.x() * b.y() - b.x() * c.y(), det);
+    let nb = Rational::new(a.x() * c.y() - c.x() * a.y(), det);
+    Some(3 * na.to_integer()? + nb.to_integer()?)
 }
 
 pub fn part_b(input: &str) -> Answer {
@@ -123,4 +103,28 @@ mod tests {
         let result = part_a(TEST_INPUT);
         assert_eq!(result, Answer::Number(480));
     }
+
+    #[test]
+    fn test_parallel_buttons() {
+        let problem = |price| Problem {
+            button_a: Vec2i::new(2, 2),
+            button_b: Vec2i::new(4, 4),
+            price,
+        };
+        // not on the line spanned by the buttons
+        assert_eq!(solve_problem(&problem(Vec2i::new(6, 8))), None);
+        // on the line, but not an integer combination
+        assert_eq!(solve_problem(&problem(Vec2i::new(3, 3))), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parallel_buttons_reachable() {
+        let problem = Problem {
+            button_a: Vec2i::new(2, 2),
+            button_b: Vec2i::new(4, 4),
+            price: Vec2i::new(6, 6),
+        };
+        solve_problem(&problem);
+    }
 }
@@ -1,4 +1,3 @@
-use crate::map2d::Map;
 use crate::math::crt2;
 use crate::{vector::Vec2i, Answer};
 
@@ -65,17 +64,7 @@ pub fn part_a(input: &str) -> Answer {
     solve_part_a(input, H, W)
 }
 
-fn plot_robots(positions: &[Vec2i], h: usize, w: usize) {
-    let mut picture = Map::<char>::new_constant(h, w, ' ');
-    for p in positions.iter() {
-        picture[p] = 'x';
-    }
-    println!("{}", picture);
-}
-
-const PLOT: bool = false;
-
-pub fn part_b(input: &str) -> Answer {
+pub fn part_b(_input: &str) -> Answer {
     // observed two series that show "patterns":
     // 65 + 103 * i
     //  9 + 101 * j
@@ -83,21 +72,13 @@ pub fn part_b(input: &str) -> Answer {
     //  x % 103 = 65
     //  x % 101 = 9
     let t = crt2(103, 65, 101, 9).unwrap();
-    if PLOT {
-        let positions = input
-            .trim()
-            .lines()
-            .map(parse_robot)
-            .map(|r| simulate_robot(r.p, r.v, 103, 101, t as usize))
-            .collect::<Vec<_>>();
-        plot_robots(&positions, H, W);
-    }
     Answer::Number(t)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::map2d::Map;
     use indoc::indoc;
 
     const TEST_INPUT: &str = indoc! {"
@@ -120,4 +101,24 @@ mod tests {
         let result = solve_part_a(TEST_INPUT, 7, 11);
         assert_eq!(result, Answer::Number(12));
     }
+
+    #[test]
+    fn test_plot_robots() {
+        let mut counts = Map::<u32>::new(7, 11);
+        for robot in TEST_INPUT.trim().lines().map(parse_robot) {
+            counts[&simulate_robot(robot.p, robot.v, 7, 11, 100)] += 1;
+        }
+        let picture = counts
+            .render_with(|n| char::from_digit(*n, 10).filter(|_| *n > 0).unwrap_or('.'))
+            .to_string();
+        let expected = indoc! {"
+            ......2..1.
+            ...........
+            1..........
+            .11........
+            .....1.....
+            ...12......
+            .1....1...."};
+        assert_eq!(picture, expected);
+    }
 }
@@ -1,6 +1,7 @@
 use crate::container::BucketQueue;
 use crate::dir::Dir;
 use crate::map2d::Map;
+use crate::parsing::identity;
 use crate::vector::Vec2i;
 use crate::Answer;
 use std::collections::VecDeque;
@@ -14,18 +15,14 @@ struct State {
 type CostMap = Map<[Option<i64>; 4]>;
 
 pub fn part_a(input: &str) -> Answer {
-    let map = Map::<char>::from_lines(input.trim().lines(), &|c| c);
-    let start = map.iter().find(|(_, v)| **v == 'S').unwrap().0;
-    let end = map.iter().find(|(_, v)| **v == 'E').unwrap().0;
+    let (map, [start, end]) = Map::parse(input, ['S', 'E'], identity).unwrap();
     let cost_map = solve_forward((start, Dir::E), end, &map);
     let optimal_cost = cost_map[&end].iter().flatten().max().unwrap();
     Answer::Number(*optimal_cost)
 }
 
 pub fn part_b(input: &str) -> Answer {
-    let map = Map::<char>::from_lines(input.trim().lines(), &|c| c);
-    let start = map.iter().find(|(_, v)| **v == 'S').unwrap().0;
-    let end = map.iter().find(|(_, v)| **v == 'E').unwrap().0;
+    let (map, [start, end]) = Map::parse(input, ['S', 'E'], identity).unwrap();
     let cost_map = solve_forward((start, Dir::E), end, &map);
     let result = solve_reverse(&cost_map, end);
     Answer::Number(result)
@@ -1,19 +1,17 @@
 use crate::Answer;
+use crate::vm::{Effect, Instruction, InstructionSet, Operand, Registers, Vm, Word};
 
-#[derive(Debug, Copy, Clone)]
-struct Registers {
-    a: u64,
-    b: u64,
-    c: u64,
-}
+const A: usize = 0;
+const B: usize = 1;
+const C: usize = 2;
 
-fn parse_input(input: &str) -> (Registers, Vec<u64>) {
+fn parse_input(input: &str) -> (Registers<3>, Vec<Word>) {
     let mut lines = input.trim().lines();
     let mut reg_parse = || {
         lines
             .next()
             .and_then(|l| l.split(": ").nth(1))
-            .and_then(|s: &str| match str::parse::<u64>(s) {
+            .and_then(|s: &str| match str::parse::<Word>(s) {
                 Ok(x) => Some(x),
                 Err(_) => None,
             })
@@ -30,57 +28,119 @@ fn parse_input(input: &str) -> (Registers, Vec<u64>) {
         .nth(1)
         .unwrap()
         .split(",")
-        .flat_map(str::parse::<u64>)
+        .flat_map(str::parse::<Word>)
         .collect::<Vec<_>>();
 
-    (Registers { a, b, c }, program)
+    (Registers::new([a, b, c]), program)
 }
 
-fn step(ptr: usize, program: &[u64], registers: &mut Registers) -> (usize, Option<u64>) {
-    let opcode = program[ptr];
-    let operand = program[ptr + 1];
-
-    let combo_op = match operand {
-        x if (0..=3).contains(&x) => x,
-        4 => registers.a,
-        5 => registers.b,
-        6 => registers.c,
-        _ => unreachable!(),
-    };
-
-    let mut stdout: Option<u64> = None;
-    match opcode {
-        0 => registers.a /= 2u64.pow(combo_op as u32),
-        1 => registers.b ^= operand,
-        2 => registers.b = combo_op % 8,
-        3 => {
-            if registers.a != 0 {
-                return (0, None);
-            }
-        }
-        4 => registers.b ^= registers.c,
-        5 => stdout = Some(combo_op % 8),
-        6 => registers.b = registers.a / 2u64.pow(combo_op as u32),
-        7 => registers.c = registers.a / 2u64.pow(combo_op as u32),
+/// Value of a combo operand: 0-3 are literals, 4-6 refer to registers a-c.
+fn combo(registers: &Registers<3>, operand: Word) -> Word {
+    match operand {
+        0..=3 => operand,
+        4..=6 => registers[operand as usize - 4],
         _ => unreachable!(),
     }
+}
 
-    (ptr + 2, stdout)
+fn format_combo(operand: Word, names: &[&'static str]) -> String {
+    match operand {
+        0..=3 => operand.to_string(),
+        4..=6 => names[operand as usize - 4].to_string(),
+        _ => "?".to_string(),
+    }
 }
 
-fn execute(program: &[u64], registers: &mut Registers) -> Vec<u64> {
-    let mut ptr = 0;
-    let mut stdout = Vec::new();
-    let mut maybe_stdout: Option<u64>;
+fn div_pow2(x: Word, e: Word) -> Word {
+    x.checked_shr(e as u32).unwrap_or(0)
+}
 
-    while ptr < program.len() {
-        (ptr, maybe_stdout) = step(ptr, program, registers);
-        if let Some(out) = maybe_stdout {
-            stdout.push(out);
-        }
-    }
+fn isa() -> InstructionSet<3> {
+    const COMBO: &[Operand] = &[Operand::Custom(format_combo)];
+    const LITERAL: &[Operand] = &[Operand::Literal];
+    const IGNORED: &[Operand] = &[Operand::Ignored];
+    InstructionSet::new(
+        ["a", "b", "c"],
+        vec![
+            Instruction {
+                opcode: 0,
+                mnemonic: "adv",
+                operands: COMBO,
+                exec: |r, ops| {
+                    r[A] = div_pow2(r[A], combo(r, ops[0]));
+                    Effect::Next
+                },
+            },
+            Instruction {
+                opcode: 1,
+                mnemonic: "bxl",
+                operands: LITERAL,
+                exec: |r, ops| {
+                    r[B] ^= ops[0];
+                    Effect::Next
+                },
+            },
+            Instruction {
+                opcode: 2,
+                mnemonic: "bst",
+                operands: COMBO,
+                exec: |r, ops| {
+                    r[B] = combo(r, ops[0]) % 8;
+                    Effect::Next
+                },
+            },
+            Instruction {
+                opcode: 3,
+                mnemonic: "jnz",
+                operands: LITERAL,
+                exec: |r, ops| match r[A] {
+                    0 => Effect::Next,
+                    _ => Effect::Jump(ops[0] as usize),
+                },
+            },
+            Instruction {
+                opcode: 4,
+                mnemonic: "bxc",
+                operands: IGNORED,
+                exec: |r, _| {
+                    r[B] ^= r[C];
+                    Effect::Next
+                },
+            },
+            Instruction {
+                opcode: 5,
+                mnemonic: "out",
+                operands: COMBO,
+                exec: |r, ops| Effect::Output(combo(r, ops[0]) % 8),
+            },
+            Instruction {
+                opcode: 6,
+                mnemonic: "bdv",
+                operands: COMBO,
+                exec: |r, ops| {
+                    r[B] = div_pow2(r[A], combo(r, ops[0]));
+                    Effect::Next
+                },
+            },
+            Instruction {
+                opcode: 7,
+                mnemonic: "cdv",
+                operands: COMBO,
+                exec: |r, ops| {
+                    r[C] = div_pow2(r[A], combo(r, ops[0]));
+                    Effect::Next
+                },
+            },
+        ],
+    )
+}
 
-    stdout
+fn execute(program: &[Word], registers: &mut Registers<3>) -> Vec<Word> {
+    let isa = isa();
+    let mut vm = Vm::new(&isa, program, *registers);
+    vm.run(usize::MAX);
+    *registers = vm.regs;
+    vm.output
 }
 
 pub fn part_a(input: &str) -> Answer {
@@ -97,94 +157,41 @@ pub fn part_a(input: &str) -> Answer {
     )
 }
 
-fn step_program(program: &[u64], registers: &mut Registers) -> (bool, u64) {
-    let mut ptr = 0;
-    let mut maybe_out: Option<u64>;
-    let mut stdout: Option<u64> = None;
-    for _ in 0..program.len() / 2 {
-        (ptr, maybe_out) = step(ptr, program, registers);
-        if let Some(out) = maybe_out {
-            assert!(stdout.is_none());
-            stdout = Some(out);
-        }
-    }
-
-    (ptr == 0, stdout.unwrap())
-}
+/// Smallest value of register a for which the program outputs a copy of itself.
+///
+/// Assumes the usual loop structure where every pass shifts a right by three bits, so the
+/// last outputs only depend on the highest octal digits of a. The digits are therefore
+/// chosen from the most significant end, keeping a candidate prefix only if running the
+/// program on it reproduces the matching suffix of the program.
+fn find_quine(isa: &InstructionSet<3>, program: &[Word], registers: &Registers<3>) -> Option<Word> {
+    let run = |a: Word| {
+        let mut regs = *registers;
+        regs[A] = a;
+        let mut vm = Vm::new(isa, program, regs);
+        // a correct candidate needs one pass per output
+        vm.run(program.len() * (program.len() + 1));
+        vm.output
+    };
 
-pub fn part_b(input: &str) -> Answer {
-    let (mut registers, program) = parse_input(input);
-    // Program facts:
-    //  8 instructions
-    //    0: 2 4  modify b              [set to a mod 8]
-    //    1: 1 1  modify b              [xor with 1 = 0001]
-    //    2: 7 5  modify c              [=a/2**b]
-    //    3: 1 5  modify b              [xor with 5 = 0101]
-    //    4: 4 2  modify b              [^= c]
-    //    5: 5 5  out                   [print register b]
-    //    6: 0 3  decrease register a   [divide by 8]
-    //    7: 3 0  jump                  [restart if register a > 0]
-
-    // a,          b,                      c
-    // a,        a%8,                      c
-    // a,    (a%8)^1,                      c
-    // a,    (a%8)^1,                      a/2^((a%8)^1)
-    // a,    (a%8)^4,                      a/2^((a%8)^1)
-    // a     ((a%8)^4)^(a/2**((a%8)^1))    a/2**((a%8)^1)
-    // a/8
-
-    // Observations:
-    // * Each run has one output.
-    // * The output only depends on the value of a.
-    //   - In particular, it only depends on the first 11 bits of a
-    // * After each run the last 3 bits of a are dropped.
-    // * After each run the pointer is reset to 0.
-    //
-    // For a program to output N numbers, the a register must be within [8^(N-1), 8^N)
-    //
-    // We build up the bits of a from the low to high, filtering out combinations
-    // that do not produce the correct series of outputs
-    let mut potential_solutions: Vec<(u64, usize)> = Vec::new();
-
-    // First initialize by detecting all combinations of first 11 bits
-    // that result in the first output value.
-    for bits_to_add in 0..2u64.pow(11) {
-        registers.a = bits_to_add;
-        let (not_finished, out) = step_program(&program, &mut registers);
-        if not_finished && out == program[0] {
-            potential_solutions.push((bits_to_add, 0));
+    let mut stack = vec![(0, program.len())];
+    while let Some((prefix, n)) = stack.pop() {
+        if n == 0 {
+            return Some(prefix);
         }
-    }
-
-    // Now iterate over final options.
-    let mut final_candidates: Vec<u64> = vec![];
-    while let Some((head, it)) = potential_solutions.pop() {
-        if head >= 8u64.pow(program.len() as u32 - 1) {
-            // number is already long enough
-            final_candidates.push(head);
-        } else {
-            for bits_to_add in 0..8 {
-                let new_value = head | bits_to_add << (11 + 3 * it);
-                registers.a = new_value >> (3 * (it + 1)); // drop tail (instead running first)
-                let (not_finished, out) = step_program(&program, &mut registers);
-                if not_finished && out == program[it + 1] {
-                    potential_solutions.push((new_value, it + 1));
-                }
+        // push in reverse so that smaller digits are explored first
+        for digit in (0..8).rev() {
+            let a = prefix << 3 | digit;
+            if a != 0 && run(a) == program[n - 1..] {
+                stack.push((a, n - 1));
             }
         }
     }
+    None
+}
 
-    // Select the best result
-    let result = final_candidates
-        .iter()
-        .filter(|c| {
-            registers.a = **c;
-            program == execute(&program, &mut registers)
-        })
-        .min()
-        .unwrap();
-
-    Answer::Number(*result as i64)
+pub fn part_b(input: &str) -> Answer {
+    let (registers, program) = parse_input(input);
+    Answer::Number(find_quine(&isa(), &program, &registers).unwrap())
 }
 
 #[cfg(test)]
@@ -194,16 +201,16 @@ mod tests {
 
     #[test]
     fn test_part_a_1() {
-        let mut registers = Registers { a: 0, b: 0, c: 9 };
+        let mut registers = Registers::new([0, 0, 9]);
         let program = vec![2, 6];
         let stdout = execute(&program, &mut registers);
-        assert_eq!(registers.b, 1);
+        assert_eq!(registers[B], 1);
         assert_eq!(stdout, vec![]);
     }
 
     #[test]
     fn test_part_a_2() {
-        let mut registers = Registers { a: 10, b: 0, c: 0 };
+        let mut registers = Registers::new([10, 0, 0]);
         let program = vec![5, 0, 5, 1, 5, 4];
         let stdout = execute(&program, &mut registers);
         assert_eq!(stdout, vec![0, 1, 2]);
@@ -231,9 +238,33 @@ mod tests {
         Program: 0,3,5,4,3,0
     "};
 
+    #[test]
+    fn test_disassemble() {
+        let (_, program) = parse_input(TEST_INPUT_B);
+        assert_eq!(
+            isa().disassemble(&program),
+            "   0: adv 3\n   2: out a\n   4: jnz 0"
+        );
+        // bxc reads its operand but ignores it
+        assert_eq!(
+            isa().disassemble(&[2, 4, 4, 7, 1, 5]),
+            "   0: bst a\n   2: bxc\n   4: bxl 5"
+        );
+    }
+
     #[test]
     fn test_part_b() {
         let result = part_b(TEST_INPUT_B);
         assert_eq!(result, Answer::Number(117440));
     }
+
+    #[test]
+    fn test_find_quine() {
+        // bst a, bxl 1, cdv b, bxl 5, bxc, out b, adv 3, jnz 0
+        let program = vec![2, 4, 1, 1, 7, 5, 1, 5, 4, 2, 5, 5, 0, 3, 3, 0];
+        let isa = isa();
+        let a = find_quine(&isa, &program, &Registers::default()).unwrap();
+        let mut registers = Registers::new([a, 0, 0]);
+        assert_eq!(execute(&program, &mut registers), program);
+    }
 }
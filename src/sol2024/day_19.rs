@@ -1,34 +1,17 @@
-use crate::trie::Trie;
 use crate::Answer;
+use crate::byte_trie::ByteTrie;
 use rayon::prelude::*;
 
-fn count_valid_patterns_dp(pattern: &str, trie: &Trie) -> u64 {
-    let n = pattern.len();
-    let mut dp = vec![0; n + 1];
-    dp[n] = 1;
-    for i in (0..n).rev() {
-        let mut node = trie.root();
-        for (k, c) in pattern[i..].chars().enumerate() {
-            match node.step(c) {
-                Some(next) => {
-                    if next.is_end() {
-                        dp[i] += dp[i + k + 1];
-                    }
-                    node = next;
-                }
-                None => {
-                    break;
-                }
-            }
-        }
-    }
-    dp[0]
-}
-
-fn parse(input: &str) -> (Trie, Vec<&str>) {
+fn parse(input: &str) -> (ByteTrie, Vec<&str>) {
     let mut overall_iter = input.trim().split("\n\n");
-    let towels = overall_iter.next().unwrap().split(",").map(|x| x.trim());
-    let trie = Trie::from_word_iterator(towels);
+    let towels = overall_iter
+        .next()
+        .unwrap()
+        .split(",")
+        .map(|x| x.trim())
+        .collect::<Vec<_>>();
+    let alphabet = towels.iter().flat_map(|t| t.bytes()).collect::<Vec<_>>();
+    let trie = ByteTrie::from_words(&alphabet, towels);
     let words = overall_iter.next().unwrap().lines().collect::<Vec<_>>();
     (trie, words)
 }
@@ -37,7 +20,7 @@ pub fn part_a(input: &str) -> Answer {
     let (trie, words) = parse(input);
     let ret = words
         .par_iter()
-        .filter(|x| count_valid_patterns_dp(x, &trie) > 0)
+        .filter(|x| trie.count_segmentations(x.as_bytes()) > 0)
         .count();
     Answer::Number(ret as i64)
 }
@@ -46,7 +29,7 @@ pub fn part_b(input: &str) -> Answer {
     let (trie, words) = parse(input);
     let ret = words
         .par_iter()
-        .map(|x| count_valid_patterns_dp(x, &trie))
+        .map(|x| trie.count_segmentations(x.as_bytes()))
         .sum::<u64>();
     Answer::Number(ret as i64)
 }
@@ -54,7 +37,9 @@ pub fn part_b(input: &str) -> Answer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::trie::Trie;
     use indoc::indoc;
+    use std::time::Instant;
 
     const TEST_INPUT: &str = indoc! {"
         r, wr, b, g, bwu, rb, gb, br
@@ -93,4 +78,52 @@ mod tests {
         let result = part_b(TEST_INPUT);
         assert_eq!(result, Answer::Number(16));
     }
+
+    /// Random input of the size of the real puzzle from a linear congruential generator.
+    fn synthetic_input() -> String {
+        let mut state: u64 = 12345;
+        let mut rand = |n: u64| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) % n
+        };
+        let mut word = |len: usize| {
+            (0..len)
+                .map(|_| b"wubrg"[rand(5) as usize] as char)
+                .collect::<String>()
+        };
+        let towels = (0..450).map(|i| word(1 + i % 8)).collect::<Vec<_>>();
+        let designs = (0..400).map(|i| word(40 + i % 20)).collect::<Vec<_>>();
+        format!("{}\n\n{}", towels.join(", "), designs.join("\n"))
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_trie_vs_byte_trie() {
+        // run with: cargo test --release bench_trie_vs_byte_trie -- --ignored --nocapture
+        let input = synthetic_input();
+        let (towels, designs) = input.split_once("\n\n").unwrap();
+        let designs = designs.lines().collect::<Vec<_>>();
+
+        let t0 = Instant::now();
+        let trie = Trie::from_word_iterator(towels.split(", "));
+        let expected = designs
+            .iter()
+            .map(|d| trie.count_segmentations(d))
+            .collect::<Vec<_>>();
+        let dt_trie = t0.elapsed();
+
+        let t0 = Instant::now();
+        let (byte_trie, _) = parse(&input);
+        let result = designs
+            .iter()
+            .map(|d| byte_trie.count_segmentations(d.as_bytes()))
+            .collect::<Vec<_>>();
+        let dt_byte_trie = t0.elapsed();
+
+        assert_eq!(result, expected);
+        println!("Trie:     {dt_trie:?}");
+        println!("ByteTrie: {dt_byte_trie:?}");
+    }
 }
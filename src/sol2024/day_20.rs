@@ -14,16 +14,14 @@ enum Tile {
 }
 
 fn parse(input: &str) -> (Vec2i, Vec2i, Map<Tile>) {
-    let map = Map::<Tile>::from_lines(input.trim().lines(), &|c| match c {
-        '.' => Tile::Free,
-        '#' => Tile::Blocked,
-        'S' => Tile::Start,
-        'E' => Tile::End,
-        _ => unreachable!(),
-    });
-
-    let start = map.iter().find(|(_, v)| **v == Tile::Start).unwrap().0;
-    let end = map.iter().find(|(_, v)| **v == Tile::End).unwrap().0;
+    let (map, [start, end]) = Map::parse(input, ['S', 'E'], |c| match c {
+        '.' => Ok(Tile::Free),
+        '#' => Ok(Tile::Blocked),
+        'S' => Ok(Tile::Start),
+        'E' => Ok(Tile::End),
+        _ => Err(format!("Unknown tile '{c}'")),
+    })
+    .unwrap();
 
     (start, end, map)
 }
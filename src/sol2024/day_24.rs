@@ -1,30 +1,5 @@
-use crate::hash::*;
 use crate::Answer;
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Op {
-    And,
-    Or,
-    Xor,
-}
-
-impl Op {
-    fn from_str(s: &str) -> Self {
-        match s {
-            "AND" => Op::And,
-            "OR" => Op::Or,
-            "XOR" => Op::Xor,
-            _ => unreachable!(),
-        }
-    }
-    fn eval(&self, left: bool, rght: bool) -> bool {
-        match self {
-            Op::And => left & rght,
-            Op::Or => left | rght,
-            Op::Xor => left ^ rght,
-        }
-    }
-}
+use crate::circuit::Circuit;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 struct Init<'a> {
@@ -41,27 +16,7 @@ impl<'a> Init<'a> {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-struct Transition<'a> {
-    left: &'a str,
-    op: Op,
-    rght: &'a str,
-    to: &'a str,
-}
-
-impl<'a> Transition<'a> {
-    fn from_line(line: &'a str) -> Self {
-        let mut spl = line.split(" ");
-        let left = spl.next().unwrap();
-        let op = Op::from_str(spl.next().unwrap());
-        let rght = spl.next().unwrap();
-        spl.next(); // space
-        let to = spl.next().unwrap();
-        Transition { left, rght, op, to }
-    }
-}
-
-fn parse(input: &str) -> (Vec<Init<'_>>, Vec<Transition<'_>>) {
+fn parse(input: &str) -> (Vec<Init<'_>>, Circuit) {
     let mut line_spl = input.trim().split("\n\n");
     let init = line_spl
         .next()
@@ -69,66 +24,49 @@ fn parse(input: &str) -> (Vec<Init<'_>>, Vec<Transition<'_>>) {
         .lines()
         .map(Init::from_line)
         .collect::<Vec<_>>();
-    let transitions = line_spl
-        .next()
-        .unwrap()
-        .lines()
-        .map(Transition::from_line)
-        .collect::<Vec<_>>();
-    (init, transitions)
+    let circuit = Circuit::parse(line_spl.next().unwrap()).unwrap();
+    (init, circuit)
 }
 
-fn simulate<'a>(
-    init: &'a Vec<Init>,
-    transitions: &'a Vec<Transition>,
-) -> Option<(u64, FxHashMap<&'a str, bool>)> {
-    let mut known_values: FxHashMap<&str, bool> = FxHashMap::new();
-    for Init { name, val } in init.iter() {
-        known_values.insert(name, *val);
-    }
-
-    let mut all_done: bool;
-    let mut z: u64 = 0;
-    loop {
-        all_done = true;
-        let mut found_new = false;
-        for Transition { left, op, rght, to } in transitions.iter() {
-            if known_values.contains_key(to) {
-                continue;
-            }
-            all_done = false;
-            if !known_values.contains_key(left) || !known_values.contains_key(rght) {
-                continue;
-            }
-            found_new = true;
-            let left_val = known_values[left];
-            let rght_val = known_values[rght];
-            let new_val = op.eval(left_val, rght_val);
-            if to.as_bytes()[0] == b'z' && new_val {
-                let idx = to[1..].parse::<u32>().unwrap();
-                z |= 1 << idx;
-            }
-            known_values.insert(to, new_val);
-        }
+pub fn part_a(input: &str) -> Answer {
+    let (init, circuit) = parse(input);
+    let inputs = init
+        .iter()
+        .filter_map(|Init { name, val }| Some((circuit.id(name)?, *val)));
+    let values = circuit.evaluate(inputs).unwrap();
+    Answer::Number(circuit.read_bus(&values, 'z').unwrap() as i64)
+}
 
-        if !found_new {
-            break;
+/// Outputs of a circuit computing z_i = x_i op y_i that are connected to the wrong bit.
+fn bitwise_violations(circuit: &Circuit) -> Vec<usize> {
+    let mut bits = vec![None; circuit.num_wires()];
+    for prefix in ['x', 'y', 'z'] {
+        for (i, w) in circuit.bus(prefix).into_iter().enumerate() {
+            bits[w] = Some(i);
         }
     }
-    if all_done {
-        Some((z, known_values))
-    } else {
-        None
-    }
+    let bit = |w: usize| bits[w];
+    circuit
+        .gates
+        .iter()
+        .filter(|g| bit(g.left) != bit(g.out) || bit(g.rght) != bit(g.out))
+        .map(|g| g.out)
+        .collect()
 }
 
-pub fn part_a(input: &str) -> Answer {
-    let (init, transitions) = parse(input);
-    Answer::Number(simulate(&init, &transitions).unwrap().0 as i64)
-}
-
-fn solve_part_b(_input: &str, _op: &str, _n: u32) -> Answer {
-    Answer::Unimplemented
+/// Find the n pairs of swapped outputs in a circuit meant to compute z = x op y.
+fn solve_part_b(input: &str, op: &str, n: usize) -> Answer {
+    let (_, circuit) = parse(input);
+    let violations = match op {
+        "add" => circuit.adder_violations(),
+        "and" => bitwise_violations(&circuit),
+        _ => unreachable!(),
+    };
+    assert_eq!(violations.len(), 2 * n);
+
+    let mut names: Vec<_> = violations.iter().map(|w| circuit.name(*w)).collect();
+    names.sort();
+    Answer::String(names.join(",").leak::<'static>())
 }
 
 pub fn part_b(input: &str) -> Answer {
@@ -240,6 +178,52 @@ mod tests {
     #[test]
     fn test_part_b() {
         let result = solve_part_b(TEST_INPUT_B, "and", 2);
-        assert_eq!(result, Answer::Unimplemented);
+        assert_eq!(result, Answer::String("z00,z01,z02,z05"));
+    }
+
+    #[test]
+    fn test_bitwise_violations_non_bus_output() {
+        // a01 ends in the right digits but is not on the z bus
+        let circuit = Circuit::parse(indoc! {"
+            x00 AND y00 -> z00
+            x01 AND y01 -> a01
+            a01 AND a01 -> z01
+        "})
+        .unwrap();
+        let names: Vec<_> = bitwise_violations(&circuit)
+            .into_iter()
+            .map(|w| circuit.name(w))
+            .collect();
+        assert_eq!(names, vec!["a01", "z01"]);
+    }
+
+    // 3-bit ripple-carry adder with the outputs of x01 XOR y01 and x01 AND y01 swapped
+    const TEST_INPUT_ADD: &str = indoc! {"
+        x00: 1
+        x01: 1
+        x02: 0
+        y00: 1
+        y01: 0
+        y02: 1
+
+        x00 XOR y00 -> z00
+        x00 AND y00 -> c01
+        x01 XOR y01 -> a01
+        s01 XOR c01 -> z01
+        x01 AND y01 -> s01
+        s01 AND c01 -> b01
+        a01 OR b01 -> c02
+        x02 XOR y02 -> s02
+        s02 XOR c02 -> z02
+        x02 AND y02 -> a02
+        s02 AND c02 -> b02
+        a02 OR b02 -> z03
+    "};
+
+    #[test]
+    fn test_part_b_add() {
+        assert_eq!(part_a(TEST_INPUT_ADD), Answer::Number(0b1010));
+        let result = solve_part_b(TEST_INPUT_ADD, "add", 1);
+        assert_eq!(result, Answer::String("a01,s01"));
     }
 }
@@ -1,42 +1,9 @@
+use crate::interval::{Interval, IntervalSet};
 use crate::Answer;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
-struct Ival {
-    lo: i64,
-    hi: i64,
-}
-
-impl Ival {
-    fn from_str(data: &str) -> Self {
-        let mut spl = data.split("-").map(|x| x.parse::<i64>().unwrap());
-        Ival {
-            lo: spl.next().unwrap(),
-            hi: spl.next().unwrap(),
-        }
-    }
-
-    fn len(&self) -> i64 {
-        std::cmp::max(0, self.hi - self.lo + 1)
-    }
-
-    fn contains(self, x: i64) -> bool {
-        self.lo <= x && x <= self.hi
-    }
-
-    fn overlap(self, other: &Ival) -> bool {
-        std::cmp::max(self.lo, other.lo) <= std::cmp::min(self.hi, other.hi)
-    }
-
-    fn merge(self, other: &Ival) -> Ival {
-        if !self.overlap(other) {
-            unreachable!("Tried to merge non-overlapping interval");
-        }
-
-        Ival {
-            lo: std::cmp::min(self.lo, other.lo),
-            hi: std::cmp::max(self.hi, other.hi),
-        }
-    }
+fn parse_interval(data: &str) -> Interval<i64> {
+    let mut spl = data.split("-").map(|x| x.parse::<i64>().unwrap());
+    Interval::closed(spl.next().unwrap(), spl.next().unwrap())
 }
 
 pub fn part_a(input: &str) -> Answer {
@@ -45,14 +12,14 @@ pub fn part_a(input: &str) -> Answer {
         .next()
         .unwrap()
         .lines()
-        .map(Ival::from_str)
-        .collect::<Vec<_>>();
+        .map(parse_interval)
+        .collect::<IntervalSet<_>>();
     let num_fresh = spl
         .next()
         .unwrap()
         .lines()
         .map(|x| x.parse::<i64>().unwrap())
-        .filter(|x| ranges.iter().any(|r| r.contains(*x)))
+        .filter(|x| ranges.contains(*x))
         .count();
 
     Answer::Number(num_fresh as i64)
@@ -60,22 +27,14 @@ pub fn part_a(input: &str) -> Answer {
 
 pub fn part_b(input: &str) -> Answer {
     let mut spl = input.trim().split("\n\n");
-    let mut ranges = spl
+    let ranges = spl
         .next()
         .unwrap()
         .lines()
-        .map(Ival::from_str)
-        .collect::<Vec<_>>();
-
-    ranges.sort();
+        .map(parse_interval)
+        .collect::<IntervalSet<_>>();
 
-    let fold_init: (i64, Ival) = (0, Ival { lo: 0, hi: -1 });
-    let folder = |(res, cur_ival): (i64, Ival), ival: &Ival| match cur_ival.overlap(ival) {
-        true => (res, cur_ival.merge(ival)),
-        false => (res + cur_ival.len(), *ival),
-    };
-    let (num_fresh, last_ival) = ranges.iter().fold(fold_init, folder);
-    Answer::Number(num_fresh + last_ival.len())
+    Answer::Number(ranges.len())
 }
 
 #[cfg(test)]
@@ -0,0 +1,268 @@
+use crate::dir::{DIRECTIONS, DIRECTIONS8, Dir};
+use crate::hash::{FxHashMap, FxHashMapBuilder};
+use crate::map2d::Map;
+use crate::vector::Vec2i;
+use std::ops::{Index, IndexMut};
+
+/// Sparse and unbounded 2D map type.
+///
+/// SparseMap<T> is indexed by Vec2i using the same "image" coordinates as Map<T>,
+/// but only stores occupied cells and allows negative coordinates. The bounding box
+/// of all occupied cells is tracked automatically.
+#[derive(Debug, Clone)]
+pub struct SparseMap<T> {
+    data: FxHashMap<Vec2i, T>,
+    bbox: Option<(Vec2i, Vec2i)>, // (min, max), inclusive
+}
+
+impl<T> Default for SparseMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseMap<T> {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        SparseMap {
+            data: FxHashMap::new(),
+            bbox: None,
+        }
+    }
+
+    /// Number of occupied cells.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check if no cells are occupied.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Bounding box (min, max) of all occupied cells, both corners inclusive.
+    pub fn bbox(&self) -> Option<(Vec2i, Vec2i)> {
+        self.bbox
+    }
+
+    /// Insert value at position, returning the previous value if there was one.
+    pub fn insert(&mut self, p: Vec2i, t: T) -> Option<T> {
+        self.bbox = Some(match self.bbox {
+            Some((min, max)) => (
+                Vec2i::new(min.x().min(p.x()), min.y().min(p.y())),
+                Vec2i::new(max.x().max(p.x()), max.y().max(p.y())),
+            ),
+            None => (p, p),
+        });
+        self.data.insert(p, t)
+    }
+
+    /// Remove value at position.
+    pub fn remove(&mut self, p: &Vec2i) -> Option<T> {
+        let ret = self.data.remove(p)?;
+        if let Some((min, max)) = self.bbox {
+            let on_edge =
+                p.x() == min.x() || p.x() == max.x() || p.y() == min.y() || p.y() == max.y();
+            if on_edge {
+                self.bbox = Self::compute_bbox(self.data.keys());
+            }
+        }
+        Some(ret)
+    }
+
+    fn compute_bbox<'a>(coords: impl Iterator<Item = &'a Vec2i>) -> Option<(Vec2i, Vec2i)> {
+        coords.fold(None, |bbox, p| match bbox {
+            Some((min, max)) => Some((
+                Vec2i::new(min.x().min(p.x()), min.y().min(p.y())),
+                Vec2i::new(max.x().max(p.x()), max.y().max(p.y())),
+            )),
+            None => Some((*p, *p)),
+        })
+    }
+
+    /// Check if position is occupied.
+    pub fn contains(&self, p: &Vec2i) -> bool {
+        self.data.contains_key(p)
+    }
+
+    /// Get map element.
+    pub fn get(&self, p: &Vec2i) -> Option<&T> {
+        self.data.get(p)
+    }
+
+    /// Get mutable map element.
+    pub fn get_mut(&mut self, p: &Vec2i) -> Option<&mut T> {
+        self.data.get_mut(p)
+    }
+
+    /// Iterate over occupied coordinates, in arbitrary order.
+    pub fn iter_coords(&self) -> impl Iterator<Item = Vec2i> {
+        self.data.keys().copied()
+    }
+
+    /// Iterate over map values, in arbitrary order.
+    pub fn iter_values(&self) -> impl Iterator<Item = &T> {
+        self.data.values()
+    }
+
+    /// Iterate over map values, in arbitrary order.
+    pub fn iter_values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.data.values_mut()
+    }
+
+    /// Iterate over (coord, val) pairs, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2i, &T)> {
+        self.data.iter().map(|(p, t)| (*p, t))
+    }
+
+    /// Iterate over (coord, val) pairs, in arbitrary order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Vec2i, &mut T)> {
+        self.data.iter_mut().map(|(p, t)| (*p, t))
+    }
+
+    /// Iterate over occupied orthogonal neighbours of a position.
+    pub fn neighbours(&self, p: &Vec2i) -> impl Iterator<Item = (Vec2i, &T)> {
+        self.neighbours_in(p, &DIRECTIONS)
+    }
+
+    /// Iterate over occupied orthogonal and diagonal neighbours of a position.
+    pub fn neighbours8(&self, p: &Vec2i) -> impl Iterator<Item = (Vec2i, &T)> {
+        self.neighbours_in(p, &DIRECTIONS8)
+    }
+
+    fn neighbours_in<'a>(
+        &'a self,
+        p: &Vec2i,
+        dirs: &'static [Dir],
+    ) -> impl Iterator<Item = (Vec2i, &'a T)> + use<'a, T> {
+        let p = *p;
+        dirs.iter().filter_map(move |d| {
+            let n = p.step(*d, 1);
+            self.data.get(&n).map(|t| (n, t))
+        })
+    }
+
+    /// Convert to a dense map covering the bounding box, filling unoccupied cells with empty.
+    ///
+    /// Returns the map together with the position of its (0, 0) cell in sparse coordinates.
+    pub fn to_map(&self, empty: T) -> (Map<T>, Vec2i)
+    where
+        T: Clone,
+    {
+        let Some((min, max)) = self.bbox else {
+            return (Map::new_constant(0, 0, empty), Vec2i::zero());
+        };
+        let h = (max.y() - min.y() + 1) as usize;
+        let w = (max.x() - min.x() + 1) as usize;
+        let mut map = Map::new_constant(h, w, empty);
+        for (p, t) in self.iter() {
+            map[&(p - min)] = t.clone();
+        }
+        (map, min)
+    }
+
+    /// Create from a dense map, keeping only cells for which keep returns true.
+    pub fn from_map_filtered<F>(map: &Map<T>, keep: F) -> Self
+    where
+        T: Clone,
+        F: Fn(&T) -> bool,
+    {
+        map.iter()
+            .filter(|(_, t)| keep(t))
+            .map(|(p, t)| (p, t.clone()))
+            .collect()
+    }
+}
+
+/// Create from a dense map, keeping all cells.
+impl<T> From<Map<T>> for SparseMap<T> {
+    fn from(map: Map<T>) -> Self {
+        let coords: Vec<_> = map.iter_coords().collect();
+        coords.into_iter().zip(map.into_values()).collect()
+    }
+}
+
+impl<T> FromIterator<(Vec2i, T)> for SparseMap<T> {
+    fn from_iter<I: IntoIterator<Item = (Vec2i, T)>>(iter: I) -> Self {
+        let mut ret = SparseMap::new();
+        for (p, t) in iter {
+            ret.insert(p, t);
+        }
+        ret
+    }
+}
+
+/// Index with Vec2i = (x, y).
+impl<T> Index<&Vec2i> for SparseMap<T> {
+    type Output = T;
+    fn index(&self, p: &Vec2i) -> &Self::Output {
+        &self.data[p]
+    }
+}
+
+/// Mutable index with Vec2i = (x, y).
+impl<T> IndexMut<&Vec2i> for SparseMap<T> {
+    fn index_mut(&mut self, p: &Vec2i) -> &mut Self::Output {
+        self.data.get_mut(p).expect("position not in map")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_map_insert_remove() {
+        let mut map = SparseMap::new();
+        assert!(map.is_empty());
+        assert_eq!(map.bbox(), None);
+
+        map.insert(Vec2i::new(-3, 2), 'a');
+        map.insert(Vec2i::new(4, -1), 'b');
+        assert_eq!(map.insert(Vec2i::new(0, 0), 'c'), None);
+        assert_eq!(map.insert(Vec2i::new(0, 0), 'd'), Some('c'));
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.bbox(), Some((Vec2i::new(-3, -1), Vec2i::new(4, 2))));
+        assert_eq!(map[&Vec2i::new(0, 0)], 'd');
+        assert_eq!(map.get(&Vec2i::new(1, 1)), None);
+
+        assert_eq!(map.remove(&Vec2i::new(4, -1)), Some('b'));
+        assert_eq!(map.remove(&Vec2i::new(4, -1)), None);
+        assert_eq!(map.bbox(), Some((Vec2i::new(-3, 0), Vec2i::new(0, 2))));
+
+        map.remove(&Vec2i::new(-3, 2));
+        map.remove(&Vec2i::new(0, 0));
+        assert_eq!(map.bbox(), None);
+    }
+
+    #[test]
+    fn test_sparse_map_neighbours() {
+        let map: SparseMap<_> = [(0, 0), (1, 0), (0, -1), (-1, -1)]
+            .into_iter()
+            .map(|(x, y)| (Vec2i::new(x, y), x + y))
+            .collect();
+
+        let mut n4: Vec<_> = map.neighbours(&Vec2i::new(0, 0)).map(|(p, _)| p).collect();
+        n4.sort();
+        assert_eq!(n4, vec![Vec2i::new(0, -1), Vec2i::new(1, 0)]);
+
+        assert_eq!(map.neighbours8(&Vec2i::new(0, 0)).count(), 3);
+        assert_eq!(map.neighbours8(&Vec2i::new(5, 5)).count(), 0);
+    }
+
+    #[test]
+    fn test_sparse_map_dense_conversion() {
+        let dense = Map::from_vecs(vec![vec!['#', '.', '.'], vec!['.', '.', '#']]);
+        let mut sparse = SparseMap::from_map_filtered(&dense, |c| *c == '#');
+        assert_eq!(sparse.len(), 2);
+
+        sparse.insert(Vec2i::new(-1, -1), '#');
+        let (map, origin) = sparse.to_map('.');
+        assert_eq!(origin, Vec2i::new(-1, -1));
+        assert_eq!(map.to_string(), "#...\n.#..\n...#");
+
+        let all = SparseMap::from(dense);
+        assert_eq!(all.len(), 6);
+        assert_eq!(all[&Vec2i::new(2, 1)], '#');
+    }
+}
@@ -1,54 +1,55 @@
 use crate::hash::*;
 
 #[derive(Debug)]
-pub struct TrieNode {
-    is_end: bool,
-    children: FxHashMap<char, TrieNode>,
+pub struct TrieNode<V> {
+    value: Option<V>,
+    children: FxHashMap<char, TrieNode<V>>,
 }
 
-impl TrieNode {
+impl<V> TrieNode<V> {
     fn new() -> Self {
         TrieNode {
-            is_end: false,
+            value: None,
             children: FxHashMap::new(),
         }
     }
 
     pub fn is_end(&self) -> bool {
-        self.is_end
+        self.value.is_some()
     }
 
-    pub fn step(&self, c: char) -> Option<&TrieNode> {
+    /// Value of the word ending at this node.
+    pub fn value(&self) -> Option<&V> {
+        self.value.as_ref()
+    }
+
+    pub fn step(&self, c: char) -> Option<&TrieNode<V>> {
         self.children.get(&c)
     }
 }
 
+/// Trie mapping words to values of type V.
 #[derive(Debug)]
-pub struct Trie {
-    root: TrieNode,
+pub struct TrieMap<V> {
+    root: TrieNode<V>,
+    len: usize,
 }
 
-impl Default for Trie {
-    fn default() -> Trie {
-        Trie {
-            root: TrieNode::new(),
-        }
+/// Trie storing a set of words.
+pub type Trie = TrieMap<()>;
+
+impl<V> Default for TrieMap<V> {
+    fn default() -> Self {
+        TrieMap::new()
     }
 }
 
 impl Trie {
-    /// Create a new trie.
-    pub fn new() -> Self {
-        Trie {
-            root: TrieNode::new(),
-        }
-    }
-
     /// Create a new trie from words.
     pub fn from_words(words: &[&str]) -> Self {
         let mut ret = Trie::new();
         for word in words {
-            ret.insert(word);
+            ret.insert(word, ());
         }
         ret
     }
@@ -60,59 +61,241 @@ impl Trie {
     {
         let mut ret = Trie::new();
         for word in words {
-            ret.insert(word);
+            ret.insert(word, ());
         }
         ret
     }
+}
 
-    /// Insert a new word into trie.
-    pub fn insert(&mut self, word: &str) {
+impl<V> TrieMap<V> {
+    /// Create a new trie.
+    pub fn new() -> Self {
+        TrieMap {
+            root: TrieNode::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of words in trie.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a word with a value, returns the previous value of the word.
+    pub fn insert(&mut self, word: &str, value: V) -> Option<V> {
         let mut current = &mut self.root;
         for c in word.chars() {
             current = current.children.entry(c).or_insert(TrieNode::new());
         }
-        current.is_end = true;
+        let prev = current.value.replace(value);
+        if prev.is_none() {
+            self.len += 1;
+        }
+        prev
+    }
+
+    /// Remove 'word' from trie, returns its value if it was contained.
+    pub fn remove(&mut self, word: &str) -> Option<V> {
+        fn remove_rec<V>(node: &mut TrieNode<V>, word: &[char]) -> Option<V> {
+            let Some((c, rest)) = word.split_first() else {
+                return node.value.take();
+            };
+            let child = node.children.get_mut(c)?;
+            let removed = remove_rec(child, rest);
+            // prune branches that no longer lead to a word
+            if child.value.is_none() && child.children.is_empty() {
+                node.children.remove(c);
+            }
+            removed
+        }
+
+        let removed = remove_rec(&mut self.root, &word.chars().collect::<Vec<_>>());
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Node reached by following 'prefix' from the root.
+    fn find_node(&self, prefix: &str) -> Option<&TrieNode<V>> {
+        prefix
+            .chars()
+            .try_fold(&self.root, |node, c| node.children.get(&c))
+    }
+
+    /// Value of 'word'.
+    pub fn get(&self, word: &str) -> Option<&V> {
+        self.find_node(word)?.value.as_ref()
+    }
+
+    /// Mutable value of 'word'.
+    pub fn get_mut(&mut self, word: &str) -> Option<&mut V> {
+        word.chars()
+            .try_fold(&mut self.root, |node, c| node.children.get_mut(&c))?
+            .value
+            .as_mut()
     }
 
     /// Check if 'word' contained in trie.
-    pub fn search(&mut self, word: &str) -> bool {
-        let mut current = &mut self.root;
+    pub fn search(&self, word: &str) -> bool {
+        self.get(word).is_some()
+    }
 
-        for c in word.chars() {
-            match current.children.get_mut(&c) {
-                Some(node) => current = node,
-                None => return false,
+    /// Check if any word starts with 'prefix'.
+    pub fn prefix(&self, prefix: &str) -> bool {
+        self.find_node(prefix).is_some()
+    }
+
+    /// Longest word that is a prefix of 's', together with its value.
+    pub fn longest_prefix_match<'a>(&self, s: &'a str) -> Option<(&'a str, &V)> {
+        let mut node = &self.root;
+        let mut ret = None;
+        for (i, c) in s.char_indices() {
+            let Some(next) = node.children.get(&c) else {
+                break;
+            };
+            node = next;
+            if let Some(value) = node.value.as_ref() {
+                ret = Some((&s[..i + c.len_utf8()], value));
             }
         }
+        ret
+    }
 
-        current.is_end
+    /// Iterate over all (word, value) pairs in lexicographic order.
+    pub fn iter(&self) -> impl Iterator<Item = (String, &V)> {
+        self.iter_prefix("")
     }
 
-    /// Check if any word starts with 'prefix'.
-    pub fn prefix(&mut self, prefix: &str) -> bool {
-        let mut current = &self.root;
+    /// Iterate over (word, value) pairs of words starting with 'prefix' in lexicographic order.
+    pub fn iter_prefix(&self, prefix: &str) -> impl Iterator<Item = (String, &V)> {
+        let mut stack: Vec<(String, &TrieNode<V>)> = self
+            .find_node(prefix)
+            .map(|node| (prefix.to_string(), node))
+            .into_iter()
+            .collect();
+        std::iter::from_fn(move || {
+            while let Some((word, node)) = stack.pop() {
+                // push in reverse so that the smallest child is visited first
+                let mut children: Vec<_> = node.children.iter().collect();
+                children.sort_by_key(|(c, _)| std::cmp::Reverse(**c));
+                for (c, child) in children {
+                    let mut child_word = word.clone();
+                    child_word.push(*c);
+                    stack.push((child_word, child));
+                }
+                if let Some(value) = node.value.as_ref() {
+                    return Some((word, value));
+                }
+            }
+            None
+        })
+    }
+
+    /// All words starting with 'prefix', in lexicographic order.
+    pub fn words_with_prefix(&self, prefix: &str) -> Vec<String> {
+        self.iter_prefix(prefix).map(|(word, _)| word).collect()
+    }
+
+    /// Byte lengths of all words that are prefixes of 's', in increasing order.
+    pub fn prefix_lengths<'a>(&'a self, s: &'a str) -> impl Iterator<Item = usize> + 'a {
+        s.char_indices()
+            .scan(&self.root, |node, (i, c)| {
+                *node = node.children.get(&c)?;
+                Some((i + c.len_utf8(), node.is_end()))
+            })
+            .filter_map(|(len, is_end)| is_end.then_some(len))
+    }
+
+    /// Number of ways to split s[i..] into words, for every char boundary i.
+    fn segmentation_counts(&self, s: &str) -> Vec<u64> {
+        let n = s.len();
+        let mut dp = vec![0u64; n + 1];
+        dp[n] = 1;
+        for i in (0..n).rev().filter(|i| s.is_char_boundary(*i)) {
+            dp[i] = self
+                .prefix_lengths(&s[i..])
+                .fold(0, |acc, len| acc.saturating_add(dp[i + len]));
+        }
+        dp
+    }
+
+    /// Number of ways to write 's' as a concatenation of words (saturating at u64::MAX).
+    pub fn count_segmentations(&self, s: &str) -> u64 {
+        self.segmentation_counts(s)[0]
+    }
+
+    /// One way to write 's' as a concatenation of words, preferring short words first.
+    pub fn segmentation<'a>(&self, s: &'a str) -> Option<Vec<&'a str>> {
+        let dp = self.segmentation_counts(s);
+        let mut ret = Vec::new();
+        let mut i = 0;
+        while i < s.len() {
+            let len = self.prefix_lengths(&s[i..]).find(|len| dp[i + len] > 0)?;
+            ret.push(&s[i..i + len]);
+            i += len;
+        }
+        Some(ret)
+    }
 
-        for c in prefix.chars() {
-            match current.children.get(&c) {
-                Some(node) => current = node,
-                None => return false,
+    /// All ways to write 's' as a concatenation of words.
+    ///
+    /// The number of segmentations can grow exponentially, see count_segmentations.
+    pub fn all_segmentations<'a>(&self, s: &'a str) -> Vec<Vec<&'a str>> {
+        fn extend<'a, V>(
+            trie: &TrieMap<V>,
+            s: &'a str,
+            i: usize,
+            dp: &[u64],
+            current: &mut Vec<&'a str>,
+            ret: &mut Vec<Vec<&'a str>>,
+        ) {
+            if i == s.len() {
+                ret.push(current.clone());
+                return;
+            }
+            for len in trie.prefix_lengths(&s[i..]) {
+                if dp[i + len] > 0 {
+                    current.push(&s[i..i + len]);
+                    extend(trie, s, i + len, dp, current, ret);
+                    current.pop();
+                }
             }
         }
 
-        true
+        let dp = self.segmentation_counts(s);
+        let mut ret = Vec::new();
+        if dp[0] > 0 {
+            extend(self, s, 0, &dp, &mut Vec::new(), &mut ret);
+        }
+        ret
     }
 
     /// Return root node
-    pub fn root(&self) -> &TrieNode {
+    pub fn root(&self) -> &TrieNode<V> {
         &self.root
     }
 
     /// Step into trie.
-    pub fn step(&self, c: char) -> Option<&TrieNode> {
+    pub fn step(&self, c: char) -> Option<&TrieNode<V>> {
         self.root.step(c)
     }
 }
 
+impl<'a, V> FromIterator<(&'a str, V)> for TrieMap<V> {
+    fn from_iter<I: IntoIterator<Item = (&'a str, V)>>(iter: I) -> Self {
+        let mut ret = TrieMap::new();
+        for (word, value) in iter {
+            ret.insert(word, value);
+        }
+        ret
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,8 +303,8 @@ mod tests {
     #[test]
     fn test_trie() {
         let mut trie = Trie::default();
-        trie.insert("hello");
-        trie.insert("helios");
+        trie.insert("hello", ());
+        trie.insert("helios", ());
         assert!(!trie.search("he"));
         assert!(trie.search("hello"));
         assert!(trie.search("helios"));
@@ -131,10 +314,10 @@ mod tests {
     #[test]
     fn test_trie_step() {
         let mut trie = Trie::default();
-        trie.insert("hello");
-        trie.insert("helios");
-        trie.insert("havana");
-        trie.insert("hel");
+        trie.insert("hello", ());
+        trie.insert("helios", ());
+        trie.insert("havana", ());
+        trie.insert("hel", ());
 
         assert!(trie.step('x').is_none());
         let h = trie.step('h').unwrap();
@@ -146,4 +329,98 @@ mod tests {
         assert!(o.is_end());
         assert!(o.step('o').is_none());
     }
+
+    #[test]
+    fn test_trie_len_remove() {
+        let mut trie = Trie::from_words(&["hello", "helios", "hel"]);
+        trie.insert("hel", ());
+        assert_eq!(trie.len(), 3);
+
+        assert_eq!(trie.remove("hel"), Some(()));
+        assert_eq!(trie.remove("hel"), None);
+        assert_eq!(trie.remove("he"), None);
+        assert_eq!(trie.len(), 2);
+        assert!(!trie.search("hel"));
+        assert!(trie.prefix("hel"));
+
+        assert_eq!(trie.remove("helios"), Some(()));
+        assert!(!trie.prefix("heli"));
+        assert!(trie.search("hello"));
+        assert_eq!(trie.remove("hello"), Some(()));
+        assert!(trie.is_empty());
+        assert!(trie.root().children.is_empty());
+    }
+
+    #[test]
+    fn test_trie_words_with_prefix() {
+        let trie = Trie::from_words(&["hello", "helios", "havana", "hel", "world"]);
+        assert_eq!(
+            trie.words_with_prefix("hel"),
+            vec!["hel", "helios", "hello"]
+        );
+        assert_eq!(trie.words_with_prefix("").len(), 5);
+        assert!(trie.words_with_prefix("x").is_empty());
+    }
+
+    #[test]
+    fn test_trie_segmentations() {
+        // towels from 2024 day 19
+        let trie = Trie::from_words(&["r", "wr", "b", "g", "bwu", "rb", "gb", "br"]);
+        assert_eq!(trie.prefix_lengths("brwrr").collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(trie.count_segmentations("brwrr"), 2);
+        assert_eq!(trie.count_segmentations("rrbgbr"), 6);
+        assert_eq!(trie.count_segmentations("ubwu"), 0);
+        assert_eq!(trie.count_segmentations(""), 1);
+
+        assert_eq!(trie.segmentation("brwrr"), Some(vec!["b", "r", "wr", "r"]));
+        assert_eq!(trie.segmentation("ubwu"), None);
+        assert_eq!(
+            trie.all_segmentations("brwrr"),
+            vec![vec!["b", "r", "wr", "r"], vec!["br", "wr", "r"]]
+        );
+        assert_eq!(trie.all_segmentations("rrbgbr").len(), 6);
+        assert!(trie.all_segmentations("bbrgwb").is_empty());
+    }
+
+    #[test]
+    fn test_trie_map() {
+        let mut trie = TrieMap::new();
+        assert_eq!(trie.insert("red", 1), None);
+        assert_eq!(trie.insert("green", 2), None);
+        assert_eq!(trie.insert("red", 3), Some(1));
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.get("red"), Some(&3));
+        assert_eq!(trie.get("re"), None);
+
+        *trie.get_mut("green").unwrap() += 10;
+        assert_eq!(trie.get("green"), Some(&12));
+        assert_eq!(trie.remove("green"), Some(12));
+        assert_eq!(trie.get("green"), None);
+    }
+
+    #[test]
+    fn test_trie_map_longest_prefix() {
+        let trie: TrieMap<usize> = [("a", 1), ("ab", 2), ("abcd", 4)].into_iter().collect();
+        assert_eq!(trie.longest_prefix_match("abcde"), Some(("abcd", &4)));
+        assert_eq!(trie.longest_prefix_match("abc"), Some(("ab", &2)));
+        assert_eq!(trie.longest_prefix_match("b"), None);
+    }
+
+    #[test]
+    fn test_trie_map_iter() {
+        let trie: TrieMap<i32> = [("banana", 3), ("apple", 1), ("band", 2), ("b", 0)]
+            .into_iter()
+            .collect();
+        let items: Vec<_> = trie.iter().collect();
+        assert_eq!(
+            items,
+            vec![
+                ("apple".to_string(), &1),
+                ("b".to_string(), &0),
+                ("banana".to_string(), &3),
+                ("band".to_string(), &2)
+            ]
+        );
+        assert_eq!(trie.iter_prefix("ban").count(), 2);
+    }
 }
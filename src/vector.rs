@@ -1,4 +1,4 @@
-use num_traits::NumAssignRef;
+use num_traits::{NumAssignRef, Signed};
 use std::iter::Sum;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub};
 use std::ops::{Index, IndexMut};
@@ -10,8 +10,43 @@ use crate::dir::Dir;
 pub trait Scalar: Copy + NumAssignRef + Neg<Output = Self> + Sum {}
 impl<T> Scalar for T where T: Copy + NumAssignRef + Neg<Output = T> + Sum {}
 
+/// Implement the by-reference and assignment variants of binary operators for a Copy
+/// type by forwarding to its by-value operators, as needed for Scalar:
+///
+/// ```text
+/// forward_ref_ops!(impl[T: Bound] Type<T>; (Add add AddAssign add_assign) ...);
+/// ```
+macro_rules! forward_ref_ops {
+    (impl[$($gen:tt)*] $ty:ty;) => {};
+    (impl[$($gen:tt)*] $ty:ty; ($op:ident $fn:ident $op_assign:ident $fn_assign:ident) $($rest:tt)*) => {
+        impl<$($gen)*> std::ops::$op<&$ty> for $ty {
+            type Output = Self;
+            fn $fn(self, rhs: &Self) -> Self::Output {
+                std::ops::$op::$fn(self, *rhs)
+            }
+        }
+
+        impl<$($gen)*> std::ops::$op_assign for $ty {
+            fn $fn_assign(&mut self, rhs: Self) {
+                *self = std::ops::$op::$fn(*self, rhs);
+            }
+        }
+
+        impl<$($gen)*> std::ops::$op_assign<&$ty> for $ty {
+            fn $fn_assign(&mut self, rhs: &Self) {
+                *self = std::ops::$op::$fn(*self, *rhs);
+            }
+        }
+
+        forward_ref_ops!(impl[$($gen)*] $ty; $($rest)*);
+    };
+}
+pub(crate) use forward_ref_ops;
+
 // Base type
 
+/// Fixed size vector. Ordered lexicographically by component, so vectors can be
+/// sorted and used as BTreeMap keys.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
 pub struct Vector<const N: usize, T: Scalar> {
     data: [T; N],
@@ -55,6 +90,104 @@ impl<const N: usize, T: Scalar> Vector<N, T> {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
         self.data.iter_mut()
     }
+
+    pub fn dot(&self, other: &Self) -> T {
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| *a * *b)
+            .sum::<T>()
+    }
+
+    /// Apply f to each component.
+    pub fn map<F: Fn(T) -> T>(&self, f: F) -> Self {
+        self.iter().map(|x| f(*x)).collect()
+    }
+
+    /// Apply f to each pair of components.
+    pub fn zip_map<F: Fn(T, T) -> T>(&self, other: &Self, f: F) -> Self {
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| f(*a, *b))
+            .collect()
+    }
+
+    /// The 2N neighbours that differ by one in a single component.
+    pub fn orthogonal_neighbours(&self) -> impl Iterator<Item = Self> + use<N, T> {
+        let p = *self;
+        (0..2 * N).map(move |i| {
+            let mut q = p;
+            match i % 2 {
+                0 => q[i / 2] -= T::one(),
+                _ => q[i / 2] += T::one(),
+            }
+            q
+        })
+    }
+
+    /// The 3^N - 1 neighbours at Chebyshev distance one, in lexicographic order.
+    pub fn chebyshev_neighbours(&self) -> impl Iterator<Item = Self> + use<N, T> {
+        let p = *self;
+        let center = (3usize.pow(N as u32) - 1) / 2;
+        (0..3usize.pow(N as u32))
+            .filter(move |i| *i != center)
+            .map(move |mut i| {
+                let mut q = p;
+                // base 3 digits of i, most significant first, give offsets -1, 0, 1
+                for k in (0..N).rev() {
+                    match i % 3 {
+                        0 => q[k] -= T::one(),
+                        2 => q[k] += T::one(),
+                        _ => {}
+                    }
+                    i /= 3;
+                }
+                q
+            })
+    }
+}
+
+impl<const N: usize, T: Scalar + PartialOrd> Vector<N, T> {
+    /// Component-wise minimum.
+    pub fn component_min(&self, other: &Self) -> Self {
+        self.zip_map(other, |a, b| if b < a { b } else { a })
+    }
+
+    /// Component-wise maximum.
+    pub fn component_max(&self, other: &Self) -> Self {
+        self.zip_map(other, |a, b| if b > a { b } else { a })
+    }
+}
+
+impl<const N: usize, T: Scalar + Signed + PartialOrd> Vector<N, T> {
+    /// Component-wise absolute value.
+    pub fn abs(&self) -> Self {
+        self.map(|x| x.abs())
+    }
+
+    /// Component-wise sign, -1, 0 or 1.
+    pub fn signum(&self) -> Self {
+        self.map(|x| x.signum())
+    }
+
+    /// Sum of absolute values (Manhattan norm).
+    pub fn l1_norm(&self) -> T {
+        self.iter().map(|x| x.abs()).sum::<T>()
+    }
+
+    /// Largest absolute value (Chebyshev norm).
+    pub fn linf_norm(&self) -> T {
+        self.iter()
+            .map(|x| x.abs())
+            .fold(T::zero(), |acc, x| if x > acc { x } else { acc })
+    }
+
+    pub fn l1_dist(&self, other: &Self) -> T {
+        (*self - *other).l1_norm()
+    }
+
+    pub fn linf_dist(&self, other: &Self) -> T {
+        (*self - *other).linf_norm()
+    }
 }
 
 // Generic traits
@@ -65,6 +198,12 @@ impl<const N: usize, T: Scalar> Default for Vector<N, T> {
     }
 }
 
+impl<const N: usize, T: Scalar> From<[T; N]> for Vector<N, T> {
+    fn from(data: [T; N]) -> Self {
+        Self { data }
+    }
+}
+
 impl<const N: usize, T: Scalar> Index<usize> for Vector<N, T> {
     type Output = T;
     fn index(&self, index: usize) -> &Self::Output {
@@ -220,6 +359,18 @@ impl<T: Scalar> Vec2<T> {
     pub fn cross(&self, other: Vec2<T>) -> T {
         self.x() * other.y() - self.y() * other.x()
     }
+
+    /// Rotate by 90 degrees, turning N into E in the "x-east, y-south" system as
+    /// Dir::turn_right does.
+    pub fn rot90_right(&self) -> Self {
+        Self::new(-self.y(), self.x())
+    }
+
+    /// Rotate by 90 degrees, turning N into W in the "x-east, y-south" system as
+    /// Dir::turn_left does.
+    pub fn rot90_left(&self) -> Self {
+        Self::new(self.y(), -self.x())
+    }
 }
 
 pub type Vec3<T> = Vector<3, T>;
@@ -240,6 +391,14 @@ impl<T: Scalar> Vec3<T> {
     pub fn z(self) -> T {
         self.data[2]
     }
+
+    pub fn cross(&self, other: Vec3<T>) -> Vec3<T> {
+        Self::new(
+            self.y() * other.z() - self.z() * other.y(),
+            self.z() * other.x() - self.x() * other.z(),
+            self.x() * other.y() - self.y() * other.x(),
+        )
+    }
 }
 
 pub type Vec4<T> = Vector<4, T>;
@@ -310,3 +469,84 @@ impl Vec2i {
         (*self - *other).manhattan_norm()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::ModInt;
+    use crate::rational::Rational;
+    use std::collections::BTreeMap;
+
+    fn is_scalar<T: Scalar>() {}
+
+    #[test]
+    fn test_scalar_types() {
+        is_scalar::<i64>();
+        is_scalar::<f64>();
+        is_scalar::<Rational<i64>>();
+        is_scalar::<ModInt<7>>();
+    }
+
+    #[test]
+    fn test_vector_norms() {
+        let v = Vec3i::new(3, -4, 1);
+        assert_eq!(v.l1_norm(), 8);
+        assert_eq!(v.linf_norm(), 4);
+        assert_eq!(v.dot(&Vec3i::new(1, 1, 1)), 0);
+        assert_eq!(v.l1_dist(&Vec3i::zero()), 8);
+        assert_eq!(Vec2i::new(1, 1).linf_dist(&Vec2i::new(-2, 3)), 3);
+        assert_eq!(v.abs(), Vec3i::new(3, 4, 1));
+        assert_eq!(v.signum(), Vec3i::new(1, -1, 1));
+        assert_eq!(v.component_min(&Vec3i::zero()), Vec3i::new(0, -4, 0));
+        assert_eq!(v.component_max(&Vec3i::zero()), Vec3i::new(3, 0, 1));
+        assert_eq!(Vec2d::new(-1.5, 2.0).l1_norm(), 3.5);
+    }
+
+    #[test]
+    fn test_vector_cross_rotate() {
+        let (x, y) = (Vec3i::new(1, 0, 0), Vec3i::new(0, 1, 0));
+        assert_eq!(x.cross(y), Vec3i::new(0, 0, 1));
+        assert_eq!(y.cross(x), Vec3i::new(0, 0, -1));
+
+        let p = Vec2i::new(2, 1);
+        for dir in [Dir::N, Dir::E, Dir::S, Dir::W] {
+            let d = Vec2i::zero().step(dir, 1);
+            assert_eq!(d.rot90_right(), Vec2i::zero().step(dir.turn_right(), 1));
+            assert_eq!(d.rot90_left(), Vec2i::zero().step(dir.turn_left(), 1));
+        }
+        assert_eq!(p.rot90_right().rot90_left(), p);
+        assert_eq!(p.rot90_right().rot90_right(), -p);
+    }
+
+    #[test]
+    fn test_vector_neighbours() {
+        let p = Vec3i::new(0, 0, 0);
+        assert_eq!(p.orthogonal_neighbours().count(), 6);
+        assert!(p.orthogonal_neighbours().all(|q| q.l1_norm() == 1));
+
+        let nbrs: Vec<_> = p.chebyshev_neighbours().collect();
+        assert_eq!(nbrs.len(), 26);
+        assert!(nbrs.iter().all(|q| q.linf_norm() == 1));
+        assert!(nbrs.is_sorted());
+
+        let nbrs: Vec<_> = Vec2i::new(5, 5).chebyshev_neighbours().collect();
+        assert_eq!(nbrs[0], Vec2i::new(4, 4));
+        assert_eq!(nbrs[3], Vec2i::new(5, 4));
+        assert_eq!(nbrs[7], Vec2i::new(6, 6));
+    }
+
+    #[test]
+    fn test_vector_order() {
+        let mut v = vec![Vec2i::new(1, 0), Vec2i::new(0, 5), Vec2i::new(0, -1)];
+        v.sort();
+        assert_eq!(
+            v,
+            vec![Vec2i::new(0, -1), Vec2i::new(0, 5), Vec2i::new(1, 0)]
+        );
+
+        let mut m = BTreeMap::new();
+        m.insert(Vector::from([2, 1, 0]), 'a');
+        m.insert(Vector::from([1, 9, 9]), 'b');
+        assert_eq!(m.values().collect::<String>(), "ba");
+    }
+}
@@ -0,0 +1,372 @@
+use crate::hash::{FxHashSet, FxHashSetBuilder};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Machine word used for registers, operands and output.
+pub type Word = i64;
+
+/// Register file with N registers.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Registers<const N: usize> {
+    pub regs: [Word; N],
+}
+
+impl<const N: usize> Registers<N> {
+    pub fn new(regs: [Word; N]) -> Self {
+        Registers { regs }
+    }
+}
+
+impl<const N: usize> Default for Registers<N> {
+    fn default() -> Self {
+        Registers { regs: [0; N] }
+    }
+}
+
+impl<const N: usize> Index<usize> for Registers<N> {
+    type Output = Word;
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.regs[index]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Registers<N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.regs[index]
+    }
+}
+
+/// Effect of executing one instruction on the control flow.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Effect {
+    /// Continue with the next instruction.
+    Next,
+    /// Continue at an absolute program position.
+    Jump(usize),
+    /// Emit a value and continue with the next instruction.
+    Output(Word),
+    /// Stop execution.
+    Halt,
+}
+
+/// How an operand is interpreted, used by the disassembler.
+#[derive(Clone, Copy)]
+pub enum Operand {
+    /// Literal value.
+    Literal,
+    /// Index into the register file.
+    Register,
+    /// Custom formatting, e.g. for operands that select a literal or a register.
+    Custom(fn(Word, &[&'static str]) -> String),
+    /// Occupies a program position but is not used, omitted from the disassembly.
+    Ignored,
+}
+
+/// Declarative description of one instruction.
+#[derive(Clone)]
+pub struct Instruction<const N: usize> {
+    pub opcode: Word,
+    pub mnemonic: &'static str,
+    pub operands: &'static [Operand],
+    pub exec: fn(&mut Registers<N>, &[Word]) -> Effect,
+}
+
+/// Instruction table together with register names.
+#[derive(Clone)]
+pub struct InstructionSet<const N: usize> {
+    pub register_names: [&'static str; N],
+    instructions: Vec<Instruction<N>>,
+}
+
+impl<const N: usize> InstructionSet<N> {
+    pub fn new(register_names: [&'static str; N], instructions: Vec<Instruction<N>>) -> Self {
+        InstructionSet {
+            register_names,
+            instructions,
+        }
+    }
+
+    /// Look up instruction by opcode.
+    pub fn get(&self, opcode: Word) -> Option<&Instruction<N>> {
+        self.instructions.iter().find(|i| i.opcode == opcode)
+    }
+
+    /// Format a single instruction with its operands.
+    pub fn format_instruction(&self, instr: &Instruction<N>, operands: &[Word]) -> String {
+        let mut ret = instr.mnemonic.to_string();
+        for (kind, val) in instr.operands.iter().zip(operands) {
+            let op = match kind {
+                Operand::Literal => val.to_string(),
+                Operand::Register => self.register_names[*val as usize].to_string(),
+                Operand::Custom(f) => f(*val, &self.register_names),
+                Operand::Ignored => continue,
+            };
+            ret.push(' ');
+            ret.push_str(&op);
+        }
+        ret
+    }
+
+    /// Print program in readable form, one instruction per line prefixed by its position.
+    pub fn disassemble(&self, program: &[Word]) -> String {
+        let mut lines = Vec::new();
+        let mut ip = 0;
+        while ip < program.len() {
+            match self.get(program[ip]) {
+                Some(instr) => {
+                    let end = (ip + 1 + instr.operands.len()).min(program.len());
+                    let text = self.format_instruction(instr, &program[ip + 1..end]);
+                    lines.push(format!("{ip:>4}: {text}"));
+                    ip = end;
+                }
+                None => {
+                    lines.push(format!("{ip:>4}: ??? {}", program[ip]));
+                    ip += 1;
+                }
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Reason why execution stopped.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StopReason {
+    /// Instruction pointer left the program or a Halt instruction was executed.
+    Halted,
+    /// Step limit reached.
+    StepLimit,
+    /// Instruction pointer reached a breakpoint.
+    Breakpoint(usize),
+    /// Unknown opcode at position.
+    InvalidOpcode(usize),
+}
+
+/// Register state around one executed instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry<const N: usize> {
+    pub ip: usize,
+    pub opcode: Word,
+    pub before: Registers<N>,
+    pub after: Registers<N>,
+}
+
+/// Interpreter executing a program with an instruction set.
+pub struct Vm<'a, const N: usize> {
+    isa: &'a InstructionSet<N>,
+    program: &'a [Word],
+    pub ip: usize,
+    pub regs: Registers<N>,
+    pub output: Vec<Word>,
+    pub steps: usize,
+    pub trace: Option<Vec<TraceEntry<N>>>,
+    breakpoints: FxHashSet<usize>,
+    halted: bool,
+}
+
+impl<'a, const N: usize> Vm<'a, N> {
+    pub fn new(isa: &'a InstructionSet<N>, program: &'a [Word], regs: Registers<N>) -> Self {
+        Vm {
+            isa,
+            program,
+            ip: 0,
+            regs,
+            output: Vec::new(),
+            steps: 0,
+            trace: None,
+            breakpoints: FxHashSet::new(),
+            halted: false,
+        }
+    }
+
+    /// Record register state for every executed instruction.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    /// Stop before executing the instruction at ip. Call step to continue past it.
+    pub fn add_breakpoint(&mut self, ip: usize) {
+        self.breakpoints.insert(ip);
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted || self.ip >= self.program.len()
+    }
+
+    /// Execute a single instruction.
+    pub fn step(&mut self) -> Result<Effect, StopReason> {
+        if self.is_halted() {
+            return Err(StopReason::Halted);
+        }
+        let ip = self.ip;
+        let opcode = self.program[ip];
+        let instr = self.isa.get(opcode).ok_or(StopReason::InvalidOpcode(ip))?;
+        let end = ip + 1 + instr.operands.len();
+        let operands = self.program.get(ip + 1..end).ok_or(StopReason::Halted)?;
+
+        let before = self.regs;
+        let effect = (instr.exec)(&mut self.regs, operands);
+        self.steps += 1;
+        self.ip = end;
+        match effect {
+            Effect::Next => {}
+            Effect::Jump(target) => self.ip = target,
+            Effect::Output(val) => self.output.push(val),
+            Effect::Halt => self.halted = true,
+        }
+
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(TraceEntry {
+                ip,
+                opcode,
+                before,
+                after: self.regs,
+            });
+        }
+        Ok(effect)
+    }
+
+    /// Run until the program halts, a breakpoint is hit or max_steps instructions are executed.
+    ///
+    /// The instruction at the current ip is always executed, so run can be called again
+    /// after stopping at a breakpoint.
+    pub fn run(&mut self, max_steps: usize) -> StopReason {
+        for n in 0..max_steps {
+            if n > 0 && self.breakpoints.contains(&self.ip) && !self.is_halted() {
+                return StopReason::Breakpoint(self.ip);
+            }
+            if let Err(reason) = self.step() {
+                return reason;
+            }
+        }
+        match self.is_halted() {
+            true => StopReason::Halted,
+            false => StopReason::StepLimit,
+        }
+    }
+
+    /// Run until the next output value is produced.
+    pub fn run_until_output(&mut self, max_steps: usize) -> Option<Word> {
+        for _ in 0..max_steps {
+            if let Effect::Output(val) = self.step().ok()? {
+                return Some(val);
+            }
+        }
+        None
+    }
+}
+
+impl<const N: usize> fmt::Display for TraceEntry<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>4}: op {} {:?} -> {:?}",
+            self.ip, self.opcode, self.before.regs, self.after.regs
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // toy machine: two registers, set/add/jump-if-nonzero/out
+    fn toy_isa() -> InstructionSet<2> {
+        InstructionSet::new(
+            ["x", "y"],
+            vec![
+                Instruction {
+                    opcode: 0,
+                    mnemonic: "set",
+                    operands: &[Operand::Register, Operand::Literal],
+                    exec: |r, ops| {
+                        r[ops[0] as usize] = ops[1];
+                        Effect::Next
+                    },
+                },
+                Instruction {
+                    opcode: 1,
+                    mnemonic: "add",
+                    operands: &[Operand::Register, Operand::Literal],
+                    exec: |r, ops| {
+                        r[ops[0] as usize] += ops[1];
+                        Effect::Next
+                    },
+                },
+                Instruction {
+                    opcode: 2,
+                    mnemonic: "jnz",
+                    operands: &[Operand::Register, Operand::Literal],
+                    exec: |r, ops| match r[ops[0] as usize] {
+                        0 => Effect::Next,
+                        _ => Effect::Jump(ops[1] as usize),
+                    },
+                },
+                Instruction {
+                    opcode: 3,
+                    mnemonic: "out",
+                    operands: &[Operand::Register],
+                    exec: |r, ops| Effect::Output(r[ops[0] as usize]),
+                },
+            ],
+        )
+    }
+
+    // count x down from 3, outputting each value
+    const PROGRAM: [Word; 11] = [0, 0, 3, 3, 0, 1, 0, -1, 2, 0, 3];
+
+    #[test]
+    fn test_vm_run() {
+        let isa = toy_isa();
+        let mut vm = Vm::new(&isa, &PROGRAM, Registers::default());
+        assert_eq!(vm.run(100), StopReason::Halted);
+        assert_eq!(vm.output, vec![3, 2, 1]);
+        assert_eq!(vm.steps, 1 + 3 * 3);
+    }
+
+    #[test]
+    fn test_vm_step_limit_and_breakpoint() {
+        let isa = toy_isa();
+        let mut vm = Vm::new(&isa, &PROGRAM, Registers::default());
+        assert_eq!(vm.run(3), StopReason::StepLimit);
+        assert_eq!(vm.output, vec![3]);
+
+        vm.add_breakpoint(3);
+        assert_eq!(vm.run(100), StopReason::Breakpoint(3));
+        assert_eq!(vm.regs[0], 2);
+        assert_eq!(vm.run(100), StopReason::Breakpoint(3));
+        assert_eq!(vm.output, vec![3, 2]);
+        assert_eq!(vm.run_until_output(100), Some(1));
+    }
+
+    #[test]
+    fn test_vm_trace() {
+        let isa = toy_isa();
+        let mut vm = Vm::new(&isa, &PROGRAM, Registers::new([0, 7])).with_trace();
+        vm.run(2);
+        let trace = vm.trace.unwrap();
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace[0].before, Registers::new([0, 7]));
+        assert_eq!(trace[0].after, Registers::new([3, 7]));
+        assert_eq!(trace[1].ip, 3);
+        assert_eq!(trace[1].to_string(), "   3: op 3 [3, 7] -> [3, 7]");
+    }
+
+    #[test]
+    fn test_vm_invalid_opcode() {
+        let isa = toy_isa();
+        let program = [0, 1, 5, 9];
+        let mut vm = Vm::new(&isa, &program, Registers::default());
+        assert_eq!(vm.run(100), StopReason::InvalidOpcode(3));
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let isa = toy_isa();
+        assert_eq!(
+            isa.disassemble(&PROGRAM),
+            "   0: set x 3\n   3: out x\n   5: add x -1\n   8: jnz x 3"
+        );
+    }
+}